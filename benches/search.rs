@@ -0,0 +1,59 @@
+// Times the look-ahead on `Board` against the same search on bitboards, at a few depths, on a busy
+// 11x11 board. Run with `cargo bench`. It fails if the two searches ever disagree on a score.
+
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use battlesnake_rusty::transposition::TranspositionTable;
+use battlesnake_rusty::{Move, bitboard, logic};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+const DEPTHS: [i32; 3] = [4, 6, 8];
+const RUNS: u32 = 50;
+
+// The fastest `search` scored every move in, out of a number of runs each on a fresh table, along with
+// the total score
+fn time(search: impl Fn(Move, &TranspositionTable) -> f64) -> (Duration, f64) {
+    let mut fastest = Duration::MAX;
+    let mut total = 0.0;
+    for _ in 0..RUNS {
+        // Setting up a table takes longer than some searches, so that's left out
+        let table = TranspositionTable::default();
+        let started = Instant::now();
+        total = Move::ALL.iter().map(|move_dir| search(*move_dir, &table)).sum::<f64>();
+        fastest = fastest.min(started.elapsed());
+    }
+    (fastest, total)
+}
+
+fn main() -> ExitCode {
+    let state = common::four_snakes();
+    let session = common::session(&state);
+    let opponents = &session.opponents;
+    let risk_limit = common::risk_limit();
+
+    println!("Every move on 11x11 with four snakes, fastest of {} runs:", RUNS);
+    for depth in DEPTHS {
+        let (board, board_score) = time(|move_dir, table| {
+            logic::evaluate_move_safety(&state.board, &state.you, &move_dir, depth, risk_limit, opponents, table)
+        });
+        let (bits, bits_score) = time(|move_dir, table| {
+            bitboard::move_safety(&state.board, &state.you, move_dir, depth, risk_limit, opponents, table).unwrap()
+        });
+        println!(
+            "  depth {}: Board {:>10.3?}, bitboard {:>10.3?}, {:.1}x faster",
+            depth,
+            board,
+            bits,
+            board.as_secs_f64() / bits.as_secs_f64()
+        );
+
+        if board_score != bits_score {
+            eprintln!("the searches disagree at depth {}: {} on Board, {} on bitboards", depth, board_score, bits_score);
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// API and Response Objects
+// See https://docs.battlesnake.com/api
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Game {
+    pub id: String,
+    pub ruleset: HashMap<String, Value>,
+    pub timeout: u32,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Board {
+    pub height: i32,
+    pub width: i32,
+    pub food: Vec<Coord>,
+    pub snakes: Vec<Battlesnake>,
+    pub hazards: Vec<Coord>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Battlesnake {
+    pub id: String,
+    pub name: String,
+    pub health: i32,
+    pub body: Vec<Coord>,
+    pub head: Coord,
+    pub length: i32,
+    pub latency: String,
+    pub shout: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Coord {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GameState {
+    pub game: Game,
+    pub turn: i32,
+    pub board: Board,
+    pub you: Battlesnake,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Move {
+    // Every move, in the order ties between them go to
+    pub const ALL: [Move; 4] = [Move::Up, Move::Down, Move::Left, Move::Right];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Move::Up => "up",
+            Move::Down => "down",
+            Move::Left => "left",
+            Move::Right => "right",
+        }
+    }
+}
+
+impl FromStr for Move {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(Move::Up),
+            "down" => Ok(Move::Down),
+            "left" => Ok(Move::Left),
+            "right" => Ok(Move::Right),
+            _ => Err(format!("unknown move: {}", s)),
+        }
+    }
+}
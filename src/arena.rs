@@ -0,0 +1,178 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use crate::cli::Args;
+use crate::rules::{self, Elimination, Ruleset, Settings};
+use crate::session::GameSession;
+use crate::strategy::{Strategy, chosen_move};
+use crate::{Battlesnake, Board, Game, GameState};
+
+// Plays complete games between our strategies locally, with no HTTP involved
+
+#[derive(Debug, Clone)]
+pub struct ArenaConfig {
+    pub width: i32,
+    pub height: i32,
+    pub settings: Settings,
+    pub timeout: u32,
+    pub max_turns: Option<i32>,
+    // Seeds the board layout and food spawns, strategies keep their own randomness
+    pub seed: u64,
+}
+
+impl Default for ArenaConfig {
+    fn default() -> Self {
+        ArenaConfig {
+            width: 11,
+            height: 11,
+            settings: Settings::default(),
+            timeout: 500,
+            max_turns: None,
+            seed: 0,
+        }
+    }
+}
+
+impl ArenaConfig {
+    // From the board and rules options the offline tools share
+    pub fn from_args(args: &Args) -> Result<ArenaConfig, String> {
+        let defaults = ArenaConfig::default();
+        let settings = Settings::default();
+
+        Ok(ArenaConfig {
+            width: args.get("width", defaults.width)?,
+            height: args.get("height", defaults.height)?,
+            settings: Settings {
+                ruleset: args.get::<Ruleset>("ruleset", settings.ruleset)?,
+                food_spawn_chance: args.get("food-spawn-chance", settings.food_spawn_chance)?,
+                minimum_food: args.get("minimum-food", settings.minimum_food)?,
+                hazard_damage_per_turn: args.get("hazard-damage", settings.hazard_damage_per_turn)?,
+            },
+            timeout: args.get("timeout", defaults.timeout)?,
+            max_turns: args.get_opt("max-turns")?,
+            seed: args.get("seed", rand::random())?,
+        })
+    }
+}
+
+// Games sharing a seed, e.g. in a tournament, may run side by side against the same server, which
+// keys its sessions by game id
+static GAMES_PLAYED: AtomicU64 = AtomicU64::new(0);
+
+// A snake in the arena. Names double as snake ids, so they must be unique within a game.
+pub struct Contestant<'a> {
+    pub name: String,
+    pub strategy: &'a dyn Strategy,
+}
+
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    // None for a draw, and for solo games which nobody wins
+    pub winner: Option<String>,
+    pub turns: i32,
+    pub eliminations: Vec<Elimination>,
+}
+
+fn state_for(game: &Game, turn: i32, board: &Board, you: &Battlesnake) -> GameState {
+    GameState {
+        game: game.clone(),
+        turn,
+        board: board.clone(),
+        you: you.clone(),
+    }
+}
+
+pub fn play_game(config: &ArenaConfig, contestants: &[Contestant]) -> Result<Outcome, String> {
+    // Like the official engine, check every snake is up and speaks the current API before starting
+    for contestant in contestants {
+        let info = contestant.strategy.info();
+        if info.get("apiversion").and_then(Value::as_str) != Some("1") {
+            return Err(format!("{} didn't answer its index with apiversion 1", contestant.name));
+        }
+    }
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let snakes: Vec<(String, String)> = contestants.iter().map(|c| (c.name.clone(), c.name.clone())).collect();
+    let mut board = rules::create_board(config.width, config.height, &snakes, &config.settings, &mut rng)?;
+
+    let game = Game {
+        id: format!(
+            "arena-{}-{}-{}",
+            std::process::id(),
+            config.seed,
+            GAMES_PLAYED.fetch_add(1, Ordering::Relaxed)
+        ),
+        ruleset: config.settings.to_ruleset_json(),
+        timeout: config.timeout,
+    };
+
+    let mut sessions: Vec<GameSession> = contestants.iter().map(|_| GameSession::default()).collect();
+    // Dead snakes vanish from the board, but still get told how the game ended
+    let mut last_seen: HashMap<String, Battlesnake> = board.snakes.iter().map(|s| (s.id.clone(), s.clone())).collect();
+
+    for (contestant, session) in contestants.iter().zip(sessions.iter_mut()) {
+        let you = &last_seen[&contestant.name];
+        session.opponents.set_latency_thresholds(contestant.strategy.weights(&game).latency);
+        contestant.strategy.start(&state_for(&game, 0, &board, you), session);
+    }
+
+    let mut turn = 0;
+    let mut eliminations = Vec::new();
+    while !rules::is_game_over(&board, &config.settings) && config.max_turns.is_none_or(|max| turn < max) {
+        let mut moves = HashMap::new();
+        let mut latencies = HashMap::new();
+
+        for (contestant, session) in contestants.iter().zip(sessions.iter_mut()) {
+            let Some(you) = board.snakes.iter().find(|s| s.id == contestant.name) else {
+                continue;
+            };
+
+            let state = state_for(&game, turn, &board, you);
+            session.opponents.observe(&state.game, &state.board, &state.you);
+
+            let started = Instant::now();
+            let response = contestant.strategy.get_move(&state, session);
+            let latency = started.elapsed().as_millis() as u32;
+
+            // A snake that's too slow gets the engine's default move, just like over HTTP
+            if latency <= config.timeout
+                && let Some(move_dir) = chosen_move(&response)
+            {
+                moves.insert(contestant.name.clone(), move_dir);
+            }
+            latencies.insert(contestant.name.clone(), latency.min(config.timeout));
+        }
+
+        for snake in &mut board.snakes {
+            if let Some(latency) = latencies.get(&snake.id) {
+                snake.latency = latency.to_string();
+            }
+        }
+
+        turn += 1;
+        eliminations.extend(rules::step(&mut board, turn, &moves, &config.settings, &mut rng));
+        for snake in &board.snakes {
+            last_seen.insert(snake.id.clone(), snake.clone());
+        }
+    }
+
+    for (contestant, session) in contestants.iter().zip(sessions.iter_mut()) {
+        let you = &last_seen[&contestant.name];
+        contestant.strategy.end(&state_for(&game, turn, &board, you), session);
+    }
+
+    let winner = match board.snakes.as_slice() {
+        [survivor] if contestants.len() > 1 => Some(survivor.id.clone()),
+        _ => None,
+    };
+
+    Ok(Outcome {
+        winner,
+        turns: turn,
+        eliminations,
+    })
+}
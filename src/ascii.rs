@@ -0,0 +1,349 @@
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+use crate::rules::{SNAKE_MAX_HEALTH, Settings};
+use crate::opponent::inferred_move;
+use crate::simulation::simulate_move;
+use crate::{Battlesnake, Board, Coord, Game, GameState, Move};
+
+// A board as text, for scenario files and debug logs:
+//
+//   turn: 12
+//   you: A
+//   A: health 90, length 5
+//   B: health 75, name rival
+//   # # # # # # #
+//   # . . * . . #
+//   # . A a a . #
+//   # ~ ~ % a . #
+//   # . B b . . #
+//   # # # # # # #
+//
+// Rows run from the top of the board (highest y) down, inside a border of walls `#`. Each snake is a
+// letter, uppercase for its head and lowercase for its body. `*` is food, `~` a hazard and `%` food in
+// a hazard. Snake lines are optional: health defaults to full, and a length longer than the snake's
+// squares stacks the rest on its tail. A body is traced from the head, so a coiled snake that could be
+// traced more than one way needs `neck <move>`, or `path <move> <move> ...` for its first few squares,
+// to say which way it goes. Ids and names holding a ',' or '"' are quoted as JSON strings. The spaces
+// between squares are optional too.
+
+const LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+fn letter(index: usize) -> char {
+    LETTERS.chars().nth(index).unwrap_or('?')
+}
+
+// The whole state: header lines, then the board
+pub fn render(state: &GameState) -> String {
+    let mut text = format!("turn: {}\n", state.turn);
+    if let Some(index) = state.board.snakes.iter().position(|s| s.id == state.you.id) {
+        text.push_str(&format!("you: {}\n", letter(index)));
+    }
+    for (index, snake) in state.board.snakes.iter().enumerate() {
+        text.push_str(&format!("{}: health {}, length {}", letter(index), snake.health, snake.length));
+        match guide(snake).as_slice() {
+            [] => {}
+            [neck] => text.push_str(&format!(", neck {}", neck.as_str())),
+            path => {
+                let path: Vec<&str> = path.iter().map(Move::as_str).collect();
+                text.push_str(&format!(", path {}", path.join(" ")));
+            }
+        }
+        text.push_str(&format!(", id {}, name {}\n", quoted(&snake.id), quoted(&snake.name)));
+    }
+    text.push_str(&render_board(&state.board));
+    text
+}
+
+// The fewest moves from the head after which tracing the rest of the body comes out the way it really
+// lies, none when tracing alone gets it right
+fn guide(snake: &Battlesnake) -> Vec<Move> {
+    let mut body = snake.body.clone();
+    body.dedup();
+    let squares: HashSet<Coord> = body.iter().cloned().collect();
+    // A snake across itself can't be drawn anyway
+    if squares.len() != body.len() {
+        return Vec::new();
+    }
+
+    let known = (1..body.len())
+        .find(|&known| {
+            let mut path = body[..known].to_vec();
+            trace(&mut path, &squares) && path == body
+        })
+        .unwrap_or(body.len());
+    body[..known].windows(2).filter_map(|pair| inferred_move(&pair[0], &pair[1])).collect()
+}
+
+// Ids and names are free text, so quote any that would split or trim differently when parsed
+fn quoted(value: &str) -> String {
+    if value.is_empty() || value.contains([',', '"']) || value.trim() != value {
+        Value::String(value.to_string()).to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn unquoted(value: &str) -> Result<String, String> {
+    if value.starts_with('"') {
+        serde_json::from_str(value).map_err(|e| format!("invalid quoted value {}: {}", value, e))
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+// A snake line's `key value` items, split on the commas outside quotes
+fn header_items(value: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let (mut start, mut quoted, mut escaped) = (0, false, false);
+    for (i, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                items.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&value[start..]);
+    items
+}
+
+// Just the squares, walls included
+pub fn render_board(board: &Board) -> String {
+    let width = board.width.max(0) as usize;
+    let height = board.height.max(0) as usize;
+    let mut squares = vec![vec!['.'; width]; height];
+    let mut put = |coord: &Coord, square: char| {
+        if coord.x >= 0 && coord.y >= 0 && (coord.x as usize) < width && (coord.y as usize) < height {
+            squares[height - 1 - coord.y as usize][coord.x as usize] = square;
+        }
+    };
+
+    // Later layers cover earlier ones, so heads are always visible
+    for hazard in &board.hazards {
+        put(hazard, '~');
+    }
+    for food in &board.food {
+        put(food, if board.hazards.contains(food) { '%' } else { '*' });
+    }
+    for (index, snake) in board.snakes.iter().enumerate() {
+        for part in snake.body.iter().skip(1) {
+            put(part, letter(index).to_ascii_lowercase());
+        }
+    }
+    for (index, snake) in board.snakes.iter().enumerate() {
+        put(&snake.head, letter(index));
+    }
+
+    let wall = vec!["#"; width + 2].join(" ");
+    let mut text = format!("{}\n", wall);
+    for row in squares {
+        let row: Vec<String> = row.into_iter().map(String::from).collect();
+        text.push_str(&format!("# {} #\n", row.join(" ")));
+    }
+    text.push_str(&wall);
+    text.push('\n');
+    text
+}
+
+#[derive(Debug, Default)]
+struct SnakeHeader {
+    health: Option<i32>,
+    length: Option<usize>,
+    neck: Option<Move>,
+    path: Option<Vec<Move>>,
+    id: Option<String>,
+    name: Option<String>,
+}
+
+fn parse_snake_header(value: &str) -> Result<SnakeHeader, String> {
+    let mut header = SnakeHeader::default();
+    for item in header_items(value).into_iter().map(str::trim).filter(|item| !item.is_empty()) {
+        let (key, value) = item.split_once(' ').ok_or_else(|| format!("expected `key value`, got {:?}", item))?;
+        let value = value.trim();
+        match key {
+            "health" => header.health = Some(value.parse().map_err(|e| format!("invalid health {:?}: {}", value, e))?),
+            "length" => header.length = Some(value.parse().map_err(|e| format!("invalid length {:?}: {}", value, e))?),
+            "neck" => header.neck = Some(value.parse()?),
+            "path" => header.path = Some(value.split_whitespace().map(str::parse).collect::<Result<_, _>>()?),
+            "id" => header.id = Some(unquoted(value)?),
+            "name" => header.name = Some(unquoted(value)?),
+            _ => return Err(format!("unknown snake property: {}", key)),
+        }
+    }
+    Ok(header)
+}
+
+// Order a snake's squares from its head by walking through adjacent squares of the same snake.
+// Coiled snakes can branch, so this backtracks, trying the square with the fewest ways on first.
+fn trace(path: &mut Vec<Coord>, squares: &HashSet<Coord>) -> bool {
+    if path.len() == squares.len() {
+        return true;
+    }
+
+    let last = path.last().unwrap().clone();
+    let onward = |coord: &Coord, path: &[Coord]| {
+        Move::ALL
+            .iter()
+            .map(|move_dir| simulate_move(coord, move_dir))
+            .filter(|next| squares.contains(next) && !path.contains(next))
+            .count()
+    };
+    let mut candidates: Vec<Coord> = Move::ALL
+        .iter()
+        .map(|move_dir| simulate_move(&last, move_dir))
+        .filter(|next| squares.contains(next) && !path.contains(next))
+        .collect();
+    candidates.sort_by_key(|next| onward(next, path));
+
+    for next in candidates {
+        path.push(next);
+        if trace(path, squares) {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+pub fn parse(text: &str) -> Result<GameState, String> {
+    let mut turn = 0;
+    let mut you = None;
+    let mut headers: HashMap<char, SnakeHeader> = HashMap::new();
+    let mut rows: Vec<Vec<char>> = Vec::new();
+
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if line.starts_with('#') {
+            rows.push(line.chars().filter(|c| !c.is_whitespace()).collect());
+            continue;
+        }
+
+        let (key, value) = line.split_once(':').ok_or_else(|| format!("expected `key: value` or a board row, got {:?}", line))?;
+        let (key, value) = (key.trim(), value.trim());
+        match key {
+            "turn" => turn = value.parse().map_err(|e| format!("invalid turn {:?}: {}", value, e))?,
+            "you" => you = Some(value.chars().next().ok_or("missing snake letter for you")?),
+            _ if key.len() == 1 && LETTERS.contains(key) => {
+                headers.insert(key.chars().next().unwrap(), parse_snake_header(value)?);
+            }
+            _ => return Err(format!("unknown header: {}", key)),
+        }
+    }
+
+    // Strip the walls around the board
+    if rows.len() < 2 || rows.iter().any(|row| row.len() < 2 || row.len() != rows[0].len() || row.last() != Some(&'#')) {
+        return Err("the board needs a full, rectangular border of walls".to_string());
+    }
+    let inner = &rows[1..rows.len() - 1];
+    let width = rows[0].len() - 2;
+    let height = inner.len();
+
+    let mut board = Board {
+        height: height as i32,
+        width: width as i32,
+        food: Vec::new(),
+        snakes: Vec::new(),
+        hazards: Vec::new(),
+    };
+    let mut heads: HashMap<char, Coord> = HashMap::new();
+    let mut bodies: HashMap<char, HashSet<Coord>> = HashMap::new();
+
+    for (row_index, row) in inner.iter().enumerate() {
+        for (x, square) in row[1..row.len() - 1].iter().enumerate() {
+            let coord = Coord {
+                x: x as i32,
+                y: (height - 1 - row_index) as i32,
+            };
+            match square {
+                '.' => {}
+                '*' => board.food.push(coord),
+                '~' => board.hazards.push(coord),
+                '%' => {
+                    board.food.push(coord.clone());
+                    board.hazards.push(coord);
+                }
+                c if c.is_ascii_uppercase() => {
+                    if heads.insert(*c, coord).is_some() {
+                        return Err(format!("snake {} has two heads", c));
+                    }
+                }
+                c if c.is_ascii_lowercase() => {
+                    bodies.entry(c.to_ascii_uppercase()).or_default().insert(coord);
+                }
+                c => return Err(format!("unknown square {:?}", c)),
+            }
+        }
+    }
+
+    let mut letters: Vec<char> = heads.keys().copied().collect();
+    letters.sort();
+    if let Some(orphan) = bodies.keys().find(|letter| !heads.contains_key(letter)) {
+        return Err(format!("snake {} has a body but no head", orphan));
+    }
+
+    for &letter in &letters {
+        let head = heads[&letter].clone();
+        let mut squares = bodies.remove(&letter).unwrap_or_default();
+        squares.insert(head.clone());
+
+        let header = headers.remove(&letter).unwrap_or_default();
+        let mut body = vec![head.clone()];
+        for move_dir in header.path.or(header.neck.map(|neck| vec![neck])).unwrap_or_default() {
+            let next = simulate_move(body.last().unwrap(), &move_dir);
+            if !squares.contains(&next) || body.contains(&next) {
+                return Err(format!("snake {}'s neck or path isn't part of its body", letter));
+            }
+            body.push(next);
+        }
+        if !trace(&mut body, &squares) {
+            return Err(format!("snake {}'s body isn't one line from its head", letter));
+        }
+
+        let length = header.length.unwrap_or(body.len());
+        if length < body.len() {
+            return Err(format!("snake {} is longer than its length of {}", letter, length));
+        }
+        while body.len() < length {
+            body.push(body.last().unwrap().clone());
+        }
+
+        board.snakes.push(Battlesnake {
+            id: header.id.unwrap_or_else(|| letter.to_string()),
+            name: header.name.unwrap_or_else(|| letter.to_string()),
+            health: header.health.unwrap_or(SNAKE_MAX_HEALTH),
+            body,
+            head,
+            length: length as i32,
+            latency: "0".to_string(),
+            shout: None,
+        });
+    }
+    if let Some(missing) = headers.keys().next() {
+        return Err(format!("snake {} isn't on the board", missing));
+    }
+
+    // Without a `you` line, we're the first snake
+    let you_index = match you {
+        Some(you) => letters
+            .iter()
+            .position(|letter| *letter == you)
+            .ok_or_else(|| format!("you are snake {}, which isn't on the board", you))?,
+        None => 0,
+    };
+    let you = board.snakes.get(you_index).cloned().ok_or("there are no snakes on the board")?;
+
+    Ok(GameState {
+        game: Game {
+            id: "ascii".to_string(),
+            ruleset: Settings::default().to_ruleset_json(),
+            timeout: 500,
+        },
+        turn,
+        board,
+        you,
+    })
+}
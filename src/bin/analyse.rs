@@ -0,0 +1,170 @@
+use std::fs;
+use std::io::{self, Read};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use battlesnake_rusty::cli::Args;
+use battlesnake_rusty::session::GameSession;
+use battlesnake_rusty::strategy::{self, Strategy};
+use battlesnake_rusty::{GameState, Move, ascii, logic};
+
+const USAGE: &str = "\
+Shows how a strategy weighs every move on one board, without starting the server. The board is
+a GameState as JSON (a /move request body, or a line from a recording) or in the text format
+described in src/ascii.rs, read from a file or from stdin.
+
+Usage: battlesnake-rusty-analyse [options] [file]
+
+Options:
+  --strategy <name>          Strategy to analyse with [default: the first registered]
+  --depth <n>                Moves to look ahead [default: 8]
+  --time <ms>                Look ever further ahead until this much time is spent, instead of --depth
+  --threads <n>              Threads to search on [default: one per core]
+";
+
+// Deep enough that only a board with nowhere left to go gets there within a time budget
+const MAX_DEPTH: i32 = 32;
+
+fn read_input(path: Option<&String>) -> Result<String, String> {
+    match path.map(String::as_str) {
+        None | Some("-") => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).map_err(|e| format!("stdin: {}", e))?;
+            Ok(text)
+        }
+        Some(path) => fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e)),
+    }
+}
+
+fn parse_state(text: &str) -> Result<GameState, String> {
+    if !text.trim_start().starts_with('{') {
+        return ascii::parse(text);
+    }
+
+    let mut value: Value = serde_json::from_str(text).map_err(|e| format!("invalid JSON: {}", e))?;
+    // A recording line holds the request under `state`
+    if value.get("board").is_none()
+        && let Some(state) = value.get_mut("state")
+    {
+        value = state.take();
+    }
+    serde_json::from_value(value).map_err(|e| format!("not a GameState: {}", e))
+}
+
+// Every depth searches in the same session, so each one starts from the positions the last one stored
+fn explain_at(strategy: &dyn Strategy, state: &GameState, depth: i32, session: &mut GameSession) -> Value {
+    session.depth = Some(depth);
+    session.scores.clear();
+    strategy.explain(state, session)
+}
+
+// Searches one move deeper at a time, stopping when the next depth likely wouldn't fit in the budget
+fn explain_within(strategy: &dyn Strategy, state: &GameState, budget: Duration, session: &mut GameSession) -> (i32, Value) {
+    let deadline = Instant::now() + budget;
+    let growth = strategy.weights(&state.game).search_growth;
+    let mut depth = 1;
+    let mut started = Instant::now();
+    let mut explanation = explain_at(strategy, state, depth, session);
+    let mut last = started.elapsed();
+
+    while depth < MAX_DEPTH {
+        // Every extra move of look-ahead costs up to about `search_growth` times the one before
+        started = Instant::now();
+        if started + last.mul_f64(growth) > deadline {
+            break;
+        }
+        depth += 1;
+        explanation = explain_at(strategy, state, depth, session);
+        last = started.elapsed();
+    }
+    (depth, explanation)
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let strategies = strategy::registered();
+    let default_name = strategies.names().next().unwrap_or_default().to_string();
+    let name: String = args.get("strategy", default_name)?;
+    let strategy = strategies.get(&name).ok_or_else(|| format!("unknown strategy: {}", name))?;
+
+    let threads = args.get("threads", std::thread::available_parallelism().map_or(1, |n| n.get()))?;
+    let state = parse_state(&read_input(args.positional().first())?)?;
+    print!("{}", ascii::render(&state));
+
+    let mut session = GameSession {
+        threads: Some(threads),
+        ..GameSession::default()
+    };
+    let weights = strategy.weights(&state.game);
+    session.opponents.set_latency_thresholds(weights.latency);
+    session.opponents.observe(&state.game, &state.board, &state.you);
+
+    let started = Instant::now();
+    let (depth, explanation) = match args.get_opt::<u64>("time")? {
+        Some(ms) => explain_within(strategy, &state, Duration::from_millis(ms), &mut session),
+        None => {
+            let depth = args.get("depth", weights.look_ahead_depth)?;
+            (depth, explain_at(strategy, &state, depth, &mut session))
+        }
+    };
+    let elapsed = started.elapsed();
+
+    let chosen = strategy::chosen_move(&explanation).ok_or("the strategy didn't choose a move")?;
+    println!();
+    println!("{} at depth {} ({} ms): {}", name, depth, elapsed.as_millis(), chosen.as_str());
+    if let Some(reason) = explanation.get("reason").and_then(Value::as_str) {
+        println!("reason: {}", reason);
+    }
+    if let Some(shout) = explanation.get("shout").and_then(Value::as_str) {
+        println!("shout: {}", shout);
+    }
+    if let Some(chance) = explanation.get("win_chance").and_then(Value::as_f64) {
+        println!("win chance: {:.0}%", chance * 100.0);
+    }
+
+    // Moves missing from the scores were ruled out before the look-ahead
+    let scores = &explanation["safety_scores"];
+    for move_dir in Move::ALL {
+        let score = match scores.get(move_dir.as_str()).and_then(Value::as_f64) {
+            Some(score) => format!("{:.0}", score),
+            None => "ruled out".to_string(),
+        };
+        let marker = if move_dir == chosen { "*" } else { " " };
+        println!("{} {:<6} {:>12}", marker, move_dir.as_str(), score);
+    }
+
+    let line = logic::principal_variation(
+        &state.board,
+        &state.you,
+        chosen,
+        depth,
+        weights.head_to_head_risk_limit,
+        &session.opponents,
+        &session.transpositions,
+    );
+    println!(
+        "principal variation: {}",
+        line.iter().map(|move_dir| move_dir.as_str()).collect::<Vec<_>>().join(" ")
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    // Strategies log every move at info, which would interleave with the analysis
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let args = Args::from_env();
+    if args.flag("help") {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
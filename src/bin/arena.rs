@@ -0,0 +1,196 @@
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use battlesnake_rusty::arena::{self, ArenaConfig, Contestant};
+use battlesnake_rusty::cli::Args;
+use battlesnake_rusty::remote::RemoteSnake;
+use battlesnake_rusty::strategy::{self, Strategies};
+use battlesnake_rusty::tournament::{self, Sprt, SprtOutcome, TournamentConfig};
+
+const USAGE: &str = "\
+Plays a complete game between our strategies locally, using the official rules.
+
+Usage: battlesnake-rusty-arena [options]
+       battlesnake-rusty-arena tournament [options]
+
+Snakes are our built-in strategies by name, or the URL of any snake served over HTTP,
+e.g. http://localhost:8000/rusty, which is refereed through its real API.
+
+The tournament plays 1v1 games between every pair of --snakes in parallel and reports
+Elo differences with 95% confidence intervals, from the first snake's point of view.
+
+Options:
+  --snakes <a,b,...>         Strategies to play, one snake each [default: rusty,cautious]
+  --width <n>                Board width [default: 11]
+  --height <n>               Board height [default: 11]
+  --ruleset <name>           standard, solo or constrictor [default: standard]
+  --food-spawn-chance <n>    Percent chance of spawning food each turn [default: 15]
+  --minimum-food <n>         Food kept on the board at all times [default: 1]
+  --hazard-damage <n>        Health lost per turn in a hazard [default: 14]
+  --timeout <ms>             Move timeout, enforced on HTTP snakes too [default: 500]
+  --max-turns <n>            Stop the game after this many turns
+  --seed <n>                 Seeds the board layout and food spawns [default: random]
+
+Tournament options:
+  --games <n>                Games per pair of snakes [default: 1000]
+  --threads <n>              Games played at once [default: number of CPUs]
+  --sprt <elo0,elo1>         Stop early once a two snake match is decided between these Elo differences
+  --alpha <p>                SPRT false positive rate [default: 0.05]
+  --beta <p>                 SPRT false negative rate [default: 0.05]
+";
+
+// Our own strategies, plus a remote snake for every url among the requested names
+fn strategies_for(names: &[String]) -> Result<Strategies, String> {
+    let mut strategies = strategy::registered();
+    for name in names {
+        if name.starts_with("http://") && strategies.get(name).is_none() {
+            strategies = strategies.register(name, RemoteSnake::new(name)?);
+        }
+    }
+    Ok(strategies)
+}
+
+// One contestant per requested strategy, numbered when the same strategy plays more than once
+fn contestants_from<'a>(strategies: &'a Strategies, names: &[String]) -> Result<Vec<Contestant<'a>>, String> {
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let strategy = strategies
+                .get(name)
+                .ok_or_else(|| format!("unknown strategy: {} (have {})", name, strategies.names().collect::<Vec<_>>().join(", ")))?;
+            let duplicated = names.iter().filter(|other| *other == name).count() > 1;
+            Ok(Contestant {
+                name: if duplicated { format!("{}-{}", name, i + 1) } else { name.clone() },
+                strategy,
+            })
+        })
+        .collect()
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let config = ArenaConfig::from_args(args)?;
+    let names = args.list("snakes", "rusty,cautious");
+    let strategies = strategies_for(&names)?;
+    let contestants = contestants_from(&strategies, &names)?;
+
+    println!(
+        "Seed {}, {}x{} {}, {} snakes",
+        config.seed,
+        config.width,
+        config.height,
+        config.settings.ruleset.as_str(),
+        contestants.len()
+    );
+
+    let outcome = arena::play_game(&config, &contestants)?;
+
+    match &outcome.winner {
+        Some(winner) => println!("Winner: {} after {} turns", winner, outcome.turns),
+        None => println!("No winner after {} turns", outcome.turns),
+    }
+    for contestant in &contestants {
+        match outcome.eliminations.iter().find(|e| e.snake_id == contestant.name) {
+            Some(elimination) => println!("  {}: {}", contestant.name, elimination),
+            None => println!("  {}: survived", contestant.name),
+        }
+    }
+
+    Ok(())
+}
+
+fn sprt_from(args: &Args) -> Result<Option<Sprt>, String> {
+    let bounds = args.list("sprt", "");
+    if bounds.is_empty() {
+        return Ok(None);
+    }
+
+    let [elo0, elo1] = bounds.as_slice() else {
+        return Err("--sprt takes two Elo differences, e.g. --sprt 0,10".to_string());
+    };
+    let parse = |elo: &String| elo.parse::<f64>().map_err(|e| format!("invalid --sprt {}: {}", elo, e));
+    Ok(Some(Sprt {
+        elo0: parse(elo0)?,
+        elo1: parse(elo1)?,
+        alpha: args.get("alpha", 0.05)?,
+        beta: args.get("beta", 0.05)?,
+    }))
+}
+
+fn run_tournament(args: &Args) -> Result<(), String> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let config = TournamentConfig {
+        arena: ArenaConfig::from_args(args)?,
+        games: args.get("games", 1000)?,
+        threads: args.get("threads", threads)?,
+        sprt: sprt_from(args)?,
+    };
+    let names = args.list("snakes", "rusty,cautious");
+    let strategies = strategies_for(&names)?;
+
+    println!(
+        "Seed {}, {}x{} {}, {} games per pair on {} threads",
+        config.arena.seed,
+        config.arena.width,
+        config.arena.height,
+        config.arena.settings.ruleset.as_str(),
+        config.games,
+        config.threads
+    );
+
+    let played = AtomicU32::new(0);
+    let standings = tournament::run(&config, &strategies, &names, |standings| {
+        // Progress every hundred games, on one line per pairing
+        if played.fetch_add(1, Ordering::Relaxed) % 100 != 99 {
+            return;
+        }
+        for pairing in &standings.pairings {
+            let llr = match config.sprt {
+                Some(sprt) if standings.sprt.is_some() => {
+                    let (lower, upper) = sprt.bounds();
+                    format!(", LLR {:.2} [{:.2}, {:.2}]", sprt.llr(&pairing.score), lower, upper)
+                }
+                _ => String::new(),
+            };
+            println!("  {} vs {}: {}{}", pairing.first, pairing.second, pairing.score, llr);
+        }
+    })?;
+
+    println!("Results:");
+    for pairing in &standings.pairings {
+        println!("  {} vs {}: {}", pairing.first, pairing.second, pairing.score);
+    }
+    if let (Some(sprt), Some(outcome)) = (config.sprt, standings.sprt) {
+        match outcome {
+            SprtOutcome::AcceptH1 => println!("SPRT: H1 accepted, the difference is at least {:+} Elo", sprt.elo1),
+            SprtOutcome::AcceptH0 => println!("SPRT: H0 accepted, the difference is at most {:+} Elo", sprt.elo0),
+            SprtOutcome::Continue => println!("SPRT: inconclusive after {} games", standings.pairings[0].score.games()),
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    // Strategies log every move at info, which drowns out the results
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let args = Args::from_env();
+    if args.flag("help") {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let result = match args.positional() {
+        [] => run(&args),
+        [mode] if mode == "tournament" => run_tournament(&args),
+        _ => Err(format!("unexpected arguments: {}", args.positional().join(" "))),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::process::ExitCode;
+
+use battlesnake_rusty::cli::Args;
+use battlesnake_rusty::recorder;
+use battlesnake_rusty::replay::{self, Verdict};
+use battlesnake_rusty::strategy;
+
+const USAGE: &str = "\
+Re-runs games recorded with RECORD_DIR through the current build and reports every turn where
+it now picks a different move. When the recorded move died within the horizon and the new one
+survives it in simulation, or the other way round, the turn is flagged.
+
+Exits with failure if any new move dies where the recorded one survived, so it can run as a
+regression test. Strategies that break ties at random can differ on a few turns by chance.
+
+Usage: battlesnake-rusty-replay [options] <recording.jsonl or directory>...
+
+Options:
+  --strategy <name>          Replay with this strategy [default: whichever played the game]
+  --horizon <n>              Turns after a move that a death counts against it [default: 5]
+";
+
+fn describe(verdict: Verdict) -> &'static str {
+    match verdict {
+        Verdict::Changed => "",
+        Verdict::AvoidsDeath => " (avoids the recorded death)",
+        Verdict::Regression => " (REGRESSION: dies in simulation)",
+    }
+}
+
+fn run(args: &Args) -> Result<bool, String> {
+    let paths = recorder::recordings(args.positional())?;
+    if paths.is_empty() {
+        return Err("no recordings given, see --help".to_string());
+    }
+    let strategy: Option<String> = args.get_opt("strategy")?;
+    let horizon = args.get("horizon", 5)?;
+    let strategies = strategy::registered();
+
+    let (mut moves, mut changed, mut avoided, mut regressions) = (0, 0, 0, 0);
+    for path in &paths {
+        let records = recorder::load(path)?;
+        if records.is_empty() {
+            continue;
+        }
+
+        for game in replay::replay(&records, &strategies, strategy.as_deref(), horizon)? {
+            let death = game.death_turn.map_or("survived".to_string(), |turn| format!("died on turn {}", turn));
+            println!(
+                "{}: {} as {} ({}), {} moves, {}, {} different",
+                path.display(),
+                game.game_id,
+                game.snake_name,
+                game.strategy,
+                game.moves,
+                death,
+                game.differences.len()
+            );
+            for difference in &game.differences {
+                println!(
+                    "  turn {}: recorded {}, now {}{}",
+                    difference.turn,
+                    difference.recorded.as_str(),
+                    difference.replayed.as_str(),
+                    describe(difference.verdict)
+                );
+            }
+
+            moves += game.moves;
+            changed += game.differences.len();
+            avoided += game.differences.iter().filter(|d| d.verdict == Verdict::AvoidsDeath).count();
+            regressions += game.differences.iter().filter(|d| d.verdict == Verdict::Regression).count();
+        }
+    }
+
+    println!(
+        "{} moves replayed, {} different, {} avoid a recorded death, {} regressions",
+        moves, changed, avoided, regressions
+    );
+    Ok(regressions == 0)
+}
+
+fn main() -> ExitCode {
+    // Strategies log every move at info, which drowns out the report
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let args = Args::from_env();
+    if args.flag("help") {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::fs;
+use std::io::Write;
+use std::process::ExitCode;
+
+use battlesnake_rusty::arena::ArenaConfig;
+use battlesnake_rusty::cli::Args;
+use battlesnake_rusty::evaluation::Evaluator;
+use battlesnake_rusty::neural::{self, DEFAULT_NETWORK_PATH, Example, Network, TrainingSample};
+use battlesnake_rusty::recorder;
+use battlesnake_rusty::strategy;
+use battlesnake_rusty::weights::Profile;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+const USAGE: &str = "\
+Exports positions labelled with how their games ended, and trains the neural network evaluation on them.
+
+Usage: battlesnake-rusty-train export [options] [<recording.jsonl or directory>...]
+       battlesnake-rusty-train fit [options] <samples.jsonl>...
+
+Export takes every position from games recorded with RECORD_DIR that went as far as /end, plus
+those of any self-play games, and writes them one per line.
+
+Export options:
+  --self-play <n>            Arena games to play and export [default: 0]
+  --snakes <a,b,...>         Strategies playing those games [default: rusty,rusty]
+  --threads <n>              Games played at once [default: number of CPUs]
+  --output <path>            Where to write the samples [default: samples.jsonl]
+  Board and rules options are as for battlesnake-rusty-arena.
+
+Fit trains a network on exported samples, holding some back to check it against, and keeps the
+network that predicts those best. The server plays it as the `neural` snake.
+
+Fit options:
+  --hidden <n>               Hidden units [default: 32]
+  --epochs <n>               Passes over the samples [default: 20]
+  --learning-rate <r>        Step size [default: 0.01]
+  --validation <fraction>    Share of the samples held back [default: 0.1]
+  --seed <n>                 Seeds the starting weights and the order of the samples [default: 0]
+  --output <path>            Where to write the network [default: network.json]
+";
+
+fn export(args: &Args, paths: &[String]) -> Result<(), String> {
+    let output: String = args.get("output", "samples.jsonl".to_string())?;
+    let mut samples = Vec::new();
+
+    for path in recorder::recordings(paths)? {
+        samples.extend(neural::from_recording(&recorder::load(&path)?));
+    }
+    println!("{} positions from recordings", samples.len());
+
+    let games = args.get("self-play", 0)?;
+    if games > 0 {
+        let config = ArenaConfig::from_args(args)?;
+        let names = args.list("snakes", "rusty,rusty");
+        let threads = args.get("threads", std::thread::available_parallelism().map_or(1, |n| n.get()))?;
+        let played = neural::from_self_play(&config, &strategy::registered(), &names, games, threads)?;
+        println!("{} positions from {} self-play games, seed {}", played.len(), games, config.seed);
+        samples.extend(played);
+    }
+
+    let mut file = fs::File::create(&output).map_err(|e| format!("{}: {}", output, e))?;
+    for sample in &samples {
+        let line = serde_json::to_string(sample).map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| format!("{}: {}", output, e))?;
+    }
+    println!("Wrote {} samples to {}", samples.len(), output);
+    Ok(())
+}
+
+fn load_samples(paths: &[String]) -> Result<Vec<TrainingSample>, String> {
+    let mut samples = Vec::new();
+    for path in paths {
+        let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        for (i, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            samples.push(serde_json::from_str(line).map_err(|e| format!("{}:{}: {}", path, i + 1, e))?);
+        }
+    }
+    Ok(samples)
+}
+
+fn fit(args: &Args, paths: &[String]) -> Result<(), String> {
+    let hidden = args.get("hidden", 32)?;
+    let epochs = args.get("epochs", 20)?;
+    let learning_rate = args.get("learning-rate", 0.01)?;
+    let validation: f64 = args.get("validation", 0.1)?;
+    let seed = args.get("seed", 0)?;
+    let output: String = args.get("output", DEFAULT_NETWORK_PATH.to_string())?;
+
+    let mut samples = load_samples(paths)?;
+    if samples.is_empty() {
+        return Err("no samples given, see --help".to_string());
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    samples.shuffle(&mut rng);
+    let held_back = ((samples.len() as f64 * validation) as usize).min(samples.len() - 1);
+    let (checks, training) = samples.split_at(held_back);
+    let examples: Vec<Example> = training.iter().map(TrainingSample::example).collect();
+    let check_examples: Vec<Example> = checks.iter().map(TrainingSample::example).collect();
+
+    // The static evaluation, to see whether the network does any better
+    let profile = Profile::from_env();
+    let baseline = checks
+        .iter()
+        .map(|s| (profile.for_game(&s.state.game).evaluation.evaluate(&s.state.board, &s.state.you) - s.result).powi(2))
+        .sum::<f64>()
+        / checks.len().max(1) as f64;
+    println!(
+        "Training on {} positions, checking on {} (static evaluation's error {:.4})",
+        examples.len(),
+        check_examples.len(),
+        baseline
+    );
+
+    let mut network = Network::new(hidden, seed);
+    let mut best = (network.error(&check_examples), network.clone());
+    for epoch in 1..=epochs {
+        let loss = network.train_epoch(&examples, learning_rate, &mut rng);
+        let error = network.error(&check_examples);
+        println!("Epoch {}: cross-entropy {:.4}, error {:.4}", epoch, loss, error);
+        if error < best.0 || check_examples.is_empty() {
+            best = (error, network.clone());
+        }
+    }
+
+    best.1.save(&output)?;
+    println!("Wrote the network with error {:.4} to {}", best.0, output);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    // Strategies log every move at info, which drowns out the progress
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let args = Args::from_env();
+    if args.flag("help") {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let result = match args.positional() {
+        [mode, paths @ ..] if mode == "export" => export(&args, paths),
+        [mode, paths @ ..] if mode == "fit" => fit(&args, paths),
+        _ => Err("expected export or fit, see --help".to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
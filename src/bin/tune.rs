@@ -0,0 +1,213 @@
+use std::fs;
+use std::process::ExitCode;
+
+use battlesnake_rusty::arena::ArenaConfig;
+use battlesnake_rusty::cli::Args;
+use battlesnake_rusty::recorder;
+use battlesnake_rusty::spsa::{self, PARAMETERS, Spsa, SpsaConfig};
+use battlesnake_rusty::texel::{self, TexelConfig};
+use battlesnake_rusty::tournament::{Score, TournamentConfig};
+use battlesnake_rusty::weights::{Profile, Weights};
+use serde_json::Value;
+
+const USAGE: &str = "\
+Tunes Rusty's weights for one ruleset by self-play, using SPSA, or fits its static evaluation
+to recorded games.
+
+Usage: battlesnake-rusty-tune [options]
+       battlesnake-rusty-tune texel [options] <recording.jsonl or directory>...
+
+Both start from the weights in the profile the server would load (Weights.toml, or the file
+WEIGHTS names).
+
+Self-play tuning: every iteration plays a batch of games between two slightly different sets of
+weights and moves towards the one that did better. The tuned profile is written after every
+iteration, and at the end along with how the tuned weights fared against the starting ones.
+
+Self-play options:
+  --iterations <n>           SPSA iterations [default: 100]
+  --games <n>                Games per iteration [default: 20]
+  --verify <n>               Games between the tuned and starting weights at the end [default: 200]
+  --learning-rate <r>        How far a one-sided batch moves each weight, in nudges [default: 1]
+  --output <path>            Where to write the tuned profile [default: Weights.tuned.toml]
+  --threads <n>              Games played at once [default: number of CPUs]
+
+Board and rules options are as for battlesnake-rusty-arena:
+  --width <n>, --height <n>, --ruleset <name>, --food-spawn-chance <n>, --minimum-food <n>,
+  --hazard-damage <n>, --max-turns <n>, --seed <n>
+
+Texel tuning labels every position in games recorded with RECORD_DIR with how the game ended,
+and fits the static evaluation's weights to predict it.
+
+Texel options:
+  --ruleset <name>           Only fit games of this ruleset, and write its table [default: all, into [default]]
+  --iterations <n>           Gradient descent steps [default: 2000]
+  --learning-rate <r>        Gradient descent step size [default: 1]
+  --output <path>            Where to write the fitted profile [default: Weights.tuned.toml]
+";
+
+fn describe(weights: &Weights) -> String {
+    PARAMETERS
+        .iter()
+        .map(|p| format!("{} {}", p.name, (p.get)(weights)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn write_profile(path: &str, profile: &Profile, notes: &[String]) -> Result<(), String> {
+    let header: String = notes.iter().map(|note| format!("# {}\n", note)).collect();
+    let contents = format!("{}\n{}", header, profile.to_toml()?);
+    fs::write(path, contents).map_err(|e| format!("couldn't write {}: {}", path, e))
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let config = TournamentConfig {
+        arena: ArenaConfig::from_args(args)?,
+        games: args.get("games", 20)?,
+        threads: args.get("threads", threads)?,
+        sprt: None,
+    };
+    let spsa_config = SpsaConfig {
+        iterations: args.get("iterations", 100)?,
+        learning_rate: args.get("learning-rate", 1.0)?,
+        seed: config.arena.seed,
+    };
+    let verify: u32 = args.get("verify", 200)?;
+    let output: String = args.get("output", "Weights.tuned.toml".to_string())?;
+
+    let profile = Profile::from_env();
+    let ruleset = config.arena.settings.ruleset.as_str();
+    let start = profile.for_ruleset(ruleset).clone();
+    let mut spsa = Spsa::new(&start, spsa_config.clone());
+    let tuned_for = format!(
+        "Tuned for {} on {}x{}: {} iterations of {} games, seed {}",
+        ruleset, config.arena.width, config.arena.height, spsa_config.iterations, config.games, config.arena.seed
+    );
+
+    println!("{}", tuned_for);
+    println!("Starting from {}", describe(&start));
+
+    while !spsa.done() {
+        // Every iteration gets its own layouts, so the weights don't fit a handful of boards
+        let batch = TournamentConfig {
+            arena: ArenaConfig {
+                seed: config.arena.seed.wrapping_add(spsa.iteration() as u64 * config.games as u64),
+                ..config.arena.clone()
+            },
+            ..config.clone()
+        };
+        let iteration = spsa.iterate(|plus, minus| spsa::self_play(&batch, &profile, plus, minus))?;
+
+        println!(
+            "Iteration {}: [{}] vs [{}]: W {} D {} L {}, now {}",
+            iteration.number,
+            describe(&iteration.plus),
+            describe(&iteration.minus),
+            iteration.score.wins,
+            iteration.score.draws,
+            iteration.score.losses,
+            describe(&iteration.weights)
+        );
+        let progress = format!("After iteration {} of {}", iteration.number, spsa_config.iterations);
+        write_profile(&output, &spsa::profile_with(&profile, &config, &iteration.weights), &[tuned_for.clone(), progress])?;
+    }
+
+    let tuned = spsa.weights();
+    let mut notes = vec![tuned_for, format!("Started from {}", describe(&start))];
+    if verify > 0 {
+        // Seeds past any the tuning played
+        let check = TournamentConfig {
+            arena: ArenaConfig {
+                seed: config.arena.seed.wrapping_add(spsa_config.iterations as u64 * config.games as u64),
+                ..config.arena.clone()
+            },
+            games: verify,
+            ..config.clone()
+        };
+        let score: Score = spsa::self_play(&check, &profile, &tuned, &start)?;
+        println!("Tuned against starting weights: {}", score);
+        notes.push(format!("Against the starting weights: {}", score));
+    }
+
+    write_profile(&output, &spsa::profile_with(&profile, &config, &tuned), &notes)?;
+    println!("Wrote {} to {}", describe(&tuned), output);
+    Ok(())
+}
+
+fn run_texel(args: &Args, paths: &[String]) -> Result<(), String> {
+    let paths = recorder::recordings(paths)?;
+    if paths.is_empty() {
+        return Err("no recordings given, see --help".to_string());
+    }
+    let ruleset: Option<String> = args.get_opt("ruleset")?;
+    let config = TexelConfig {
+        iterations: args.get("iterations", TexelConfig::default().iterations)?,
+        learning_rate: args.get("learning-rate", TexelConfig::default().learning_rate)?,
+    };
+    let output: String = args.get("output", "Weights.tuned.toml".to_string())?;
+
+    let (mut samples, mut games) = (Vec::new(), 0);
+    for path in &paths {
+        let mut records = recorder::load(path)?;
+        if let Some(ruleset) = &ruleset {
+            records.retain(|record| record.state.game.ruleset.get("name").and_then(Value::as_str) == Some(ruleset));
+        }
+        let found = texel::samples(&records);
+        games += !found.is_empty() as usize;
+        samples.extend(found);
+    }
+    if samples.is_empty() {
+        return Err("no finished games against other snakes in the recordings".to_string());
+    }
+
+    let mut profile = Profile::from_env();
+    let mut weights = ruleset.as_deref().map_or(&profile.default, |r| profile.for_ruleset(r)).clone();
+    let before = texel::error(&weights.evaluation, &samples);
+    weights.evaluation = texel::tune(&weights.evaluation, &samples, &config);
+    let after = texel::error(&weights.evaluation, &samples);
+
+    let fitted = format!(
+        "Evaluation fitted to {} positions from {} games{}",
+        samples.len(),
+        games,
+        ruleset.as_ref().map_or(String::new(), |r| format!(" of {}", r))
+    );
+    let error = format!("Mean squared error {:.4}, down from {:.4}", after, before);
+    println!("{}", fitted);
+    println!("{}", error);
+    println!("{:?}", weights.evaluation);
+
+    match ruleset {
+        Some(ruleset) => {
+            profile.rulesets.insert(ruleset, weights);
+        }
+        None => profile.default = weights,
+    }
+    write_profile(&output, &profile, &[fitted, error])?;
+    println!("Wrote {}", output);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    // Strategies log every move at info, which drowns out the results
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let args = Args::from_env();
+    if args.flag("help") {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    let result = match args.positional() {
+        [] => run(&args),
+        [mode, paths @ ..] if mode == "texel" => run_texel(&args, paths),
+        _ => Err(format!("unexpected arguments: {}", args.positional().join(" "))),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,794 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::opponent::{MoveTraits, OpponentModel, Predictor};
+use crate::transposition::{self, Key, TranspositionTable};
+use crate::{Battlesnake, Board, Move};
+
+// A compact copy of the board for the look-ahead. Squares are numbered `y * width + x`, which bodies are
+// ring buffers of, and which occupancy and food are bitsets over. Snakes are numbered instead of carried
+// around by id: we're always snake 0, and opponents follow in the order the board lists them.
+//
+// The search here mirrors `logic::evaluate_move_safety` and `simulation::advance` step for step, down to
+// the order floating point sums are taken in, so both give exactly the same scores. Both also share a
+// transposition table, and keep positions they've already scored in it.
+
+// No square in that direction, in the neighbour table
+const OFF_BOARD: u16 = u16::MAX;
+// Per-snake bookkeeping lives in fixed arrays to keep the search from allocating.
+// Busier boards are left to the search on `Board`.
+const MAX_SNAKES: usize = 16;
+
+pub trait Bits: Clone + Debug + Send + Sync {
+    fn empty(squares: usize) -> Self;
+    fn get(&self, square: u16) -> bool;
+    fn set(&mut self, square: u16);
+    fn unset(&mut self, square: u16);
+    fn squares(&self) -> impl Iterator<Item = u16>;
+}
+
+// Boards of up to 128 squares, which covers 11x11
+impl Bits for u128 {
+    fn empty(_squares: usize) -> Self {
+        0
+    }
+
+    fn get(&self, square: u16) -> bool {
+        (self >> square) & 1 != 0
+    }
+
+    fn set(&mut self, square: u16) {
+        *self |= 1 << square;
+    }
+
+    fn unset(&mut self, square: u16) {
+        *self &= !(1 << square);
+    }
+
+    fn squares(&self) -> impl Iterator<Item = u16> {
+        let mut bits = *self;
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let square = bits.trailing_zeros() as u16;
+            bits &= bits - 1;
+            Some(square)
+        })
+    }
+}
+
+// Anything bigger, e.g. 19x19
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WideBits(Vec<u128>);
+
+impl Bits for WideBits {
+    fn empty(squares: usize) -> Self {
+        WideBits(vec![0; squares.div_ceil(128)])
+    }
+
+    fn get(&self, square: u16) -> bool {
+        self.0[square as usize / 128].get(square % 128)
+    }
+
+    fn set(&mut self, square: u16) {
+        self.0[square as usize / 128].set(square % 128);
+    }
+
+    fn unset(&mut self, square: u16) {
+        self.0[square as usize / 128].unset(square % 128);
+    }
+
+    fn squares(&self) -> impl Iterator<Item = u16> {
+        self.0
+            .iter()
+            .enumerate()
+            .flat_map(|(word, bits)| bits.squares().map(move |square| word as u16 * 128 + square))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Grid {
+    pub width: i32,
+    pub height: i32,
+    neighbours: Vec<[u16; 4]>,
+    // Each square's x and y, since dividing by the width is slow enough to show up in the search
+    coords: Vec<(i32, i32)>,
+}
+
+impl Grid {
+    pub fn new(width: i32, height: i32) -> Grid {
+        let mut neighbours = Vec::new();
+        let mut coords = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                coords.push((x, y));
+                neighbours.push(Move::ALL.map(|move_dir| {
+                    let (nx, ny) = match move_dir {
+                        Move::Up => (x, y + 1),
+                        Move::Down => (x, y - 1),
+                        Move::Left => (x - 1, y),
+                        Move::Right => (x + 1, y),
+                    };
+                    if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                        OFF_BOARD
+                    } else {
+                        (ny * width + nx) as u16
+                    }
+                }));
+            }
+        }
+        Grid {
+            width,
+            height,
+            neighbours,
+            coords,
+        }
+    }
+
+    pub fn squares(&self) -> usize {
+        self.neighbours.len()
+    }
+
+    pub fn square(&self, x: i32, y: i32) -> Option<u16> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        Some((y * self.width + x) as u16)
+    }
+
+    pub fn coords(&self, square: u16) -> (i32, i32) {
+        self.coords[square as usize]
+    }
+
+    pub fn neighbour(&self, square: u16, move_dir: Move) -> Option<u16> {
+        let index = match move_dir {
+            Move::Up => 0,
+            Move::Down => 1,
+            Move::Left => 2,
+            Move::Right => 3,
+        };
+        let neighbour = self.neighbours[square as usize][index];
+        (neighbour != OFF_BOARD).then_some(neighbour)
+    }
+
+    pub fn distance(&self, a: u16, b: u16) -> i32 {
+        let (ax, ay) = self.coords(a);
+        let (bx, by) = self.coords(b);
+        (ax - bx).abs() + (ay - by).abs()
+    }
+
+    fn snake_key(&self, snake: &Snake) -> Key {
+        transposition::snake_key(snake.id, snake.body.iter().map(|&square| self.coords(square)), snake.length)
+    }
+
+    fn on_wall(&self, square: u16) -> bool {
+        let (x, y) = self.coords(square);
+        x == 0 || y == 0 || x == self.width - 1 || y == self.height - 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Snake {
+    // Which snake this is, 0 for us
+    pub index: usize,
+    // What its parts are keyed by, see `transposition::snake`
+    pub id: u64,
+    // Head first
+    pub body: VecDeque<u16>,
+    pub health: i32,
+    pub length: i32,
+}
+
+impl Snake {
+    pub fn head(&self) -> u16 {
+        self.body[0]
+    }
+
+    pub fn tail(&self) -> u16 {
+        self.body[self.body.len() - 1]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Position<B: Bits> {
+    // We come first for as long as we're alive
+    pub snakes: Vec<Snake>,
+    pub occupied: Occupancy<B>,
+    // The tails of snakes longer than their head
+    pub tails: B,
+    pub food: B,
+    // Kept up to date as moves are made and taken back
+    pub key: Key,
+}
+
+// Every square with a body segment on it. Segments are counted, so a square stays occupied while any
+// snake is still on it, and moving a snake only touches its head and tail.
+#[derive(Debug, Clone)]
+pub struct Occupancy<B: Bits> {
+    bits: B,
+    segments: Vec<u16>,
+}
+
+impl<B: Bits> Occupancy<B> {
+    fn new(squares: usize) -> Occupancy<B> {
+        Occupancy {
+            bits: B::empty(squares),
+            segments: vec![0; squares],
+        }
+    }
+
+    pub fn get(&self, square: u16) -> bool {
+        self.bits.get(square)
+    }
+
+    fn add(&mut self, square: u16) {
+        self.segments[square as usize] += 1;
+        self.bits.set(square);
+    }
+
+    fn remove(&mut self, square: u16) {
+        self.segments[square as usize] -= 1;
+        if self.segments[square as usize] == 0 {
+            self.bits.unset(square);
+        }
+    }
+}
+
+impl<B: Bits> Position<B> {
+    pub fn us(&self) -> Option<&Snake> {
+        self.snakes.first().filter(|snake| snake.index == 0)
+    }
+
+    fn rebuild_tails(&mut self, grid: &Grid) {
+        self.tails = B::empty(grid.squares());
+        for snake in self.snakes.iter().filter(|snake| snake.body.len() > 1) {
+            self.tails.set(snake.tail());
+        }
+    }
+
+    // Whether a body segment is on `square`, leaving out the tails `tail_moves` says are on their way
+    fn blocked(&self, square: u16, tail_moves: impl Fn(&Snake) -> bool) -> bool {
+        if !self.occupied.get(square) {
+            return false;
+        }
+        // Most occupied squares aren't a tail, so only look through bodies when one is
+        if !self.tails.get(square) {
+            return true;
+        }
+
+        self.snakes.iter().any(|snake| {
+            let tail = snake.body.len() - 1;
+            snake
+                .body
+                .iter()
+                .enumerate()
+                .any(|(i, &part)| part == square && !(i == tail && snake.body.len() > 1 && tail_moves(snake)))
+        })
+    }
+}
+
+// A set of the signs of x and y steps
+#[derive(Debug, Clone, Copy, Default)]
+struct Towards(u8);
+
+impl Towards {
+    fn add(&mut self, dx: i32, dy: i32) {
+        self.0 |= Towards::bits(dx, dy);
+    }
+
+    fn contains(&self, dx: i32, dy: i32) -> bool {
+        self.0 & Towards::bits(dx, dy) != 0
+    }
+
+    fn bits(dx: i32, dy: i32) -> u8 {
+        (dx > 0) as u8 | ((dx < 0) as u8) << 1 | ((dy > 0) as u8) << 2 | ((dy < 0) as u8) << 3
+    }
+}
+
+// Every opponent's distribution in a position, indexed like its snakes, with nothing for us
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Predictions([Distribution; MAX_SNAKES]);
+
+// Where a snake's plausible moves take it and how likely each is, in Up, Down, Left, Right order
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Distribution {
+    squares: [u16; 4],
+    probabilities: [f64; 4],
+    len: usize,
+}
+
+impl Distribution {
+    fn certain(square: u16) -> Distribution {
+        Distribution {
+            squares: [square; 4],
+            probabilities: [1.0, 0.0, 0.0, 0.0],
+            len: 1,
+        }
+    }
+
+    // Moves the snake won't survive have no chance at all, and are left out
+    pub fn moves(&self) -> impl Iterator<Item = (u16, f64)> + '_ {
+        self.squares[..self.len]
+            .iter()
+            .copied()
+            .zip(self.probabilities)
+            .filter(|(_, p)| *p > 0.0)
+    }
+
+    // Ties go to the first move
+    pub fn most_likely(&self) -> Option<u16> {
+        let mut best: Option<(u16, f64)> = None;
+        for (square, probability) in self.moves() {
+            if best.is_none_or(|(_, p)| probability > p) {
+                best = Some((square, probability));
+            }
+        }
+        best.map(|(square, _)| square)
+    }
+}
+
+// The board, our snake and what we expect of every opponent, ready to search
+pub struct Search<B: Bits> {
+    pub grid: Grid,
+    pub root: Position<B>,
+    predictors: Vec<Predictor>,
+    // How likely a longer snake has to be to move onto a square for it to count as unsafe
+    risk_limit: f64,
+    // Once set, the search gives up, and leaves out of the table whatever it didn't finish
+    stop: Option<Arc<AtomicBool>>,
+}
+
+impl<B: Bits> Search<B> {
+    // None for a board with something off it or a snake without a body, which only the search on
+    // `Board` copes with
+    pub fn new(board: &Board, you: &Battlesnake, opponents: &OpponentModel, risk_limit: f64) -> Option<Search<B>> {
+        let grid = Grid::new(board.width.max(0), board.height.max(0));
+        if grid.squares() > u16::MAX as usize || board.snakes.len() > MAX_SNAKES {
+            return None;
+        }
+
+        // Opponents see the board's copy of us, so it has to be the same snake
+        let on_board = board.snakes.iter().find(|snake| snake.id == you.id)?;
+        if on_board.head != you.head || on_board.body != you.body || on_board.length != you.length {
+            return None;
+        }
+
+        let to_snake = |index: usize, snake: &Battlesnake| {
+            let body = snake
+                .body
+                .iter()
+                .map(|part| grid.square(part.x, part.y))
+                .collect::<Option<VecDeque<u16>>>()?;
+            if body.front().map(|&head| grid.coords(head)) != Some((snake.head.x, snake.head.y)) {
+                return None;
+            }
+            Some(Snake {
+                index,
+                id: transposition::snake(&snake.id),
+                body,
+                health: snake.health,
+                length: snake.length,
+            })
+        };
+
+        let mut snakes = vec![to_snake(0, you)?];
+        let mut predictors = vec![opponents.predictor(&you.id)];
+        for snake in board.snakes.iter().filter(|snake| snake.id != you.id) {
+            snakes.push(to_snake(snakes.len(), snake)?);
+            predictors.push(opponents.predictor(&snake.id));
+        }
+
+        let mut food = B::empty(grid.squares());
+        for item in &board.food {
+            food.set(grid.square(item.x, item.y)?);
+        }
+
+        let mut root = Position {
+            snakes,
+            occupied: Occupancy::new(grid.squares()),
+            tails: B::empty(grid.squares()),
+            food,
+            key: 0,
+        };
+        for square in root.snakes.iter().flat_map(|snake| snake.body.iter()) {
+            root.occupied.add(*square);
+        }
+        root.rebuild_tails(&grid);
+        for snake in &root.snakes {
+            root.key ^= grid.snake_key(snake);
+        }
+        for square in root.food.squares() {
+            root.key ^= transposition::food(grid.coords(square));
+        }
+        Some(Search {
+            grid,
+            root,
+            predictors,
+            risk_limit,
+            stop: None,
+        })
+    }
+
+    pub fn with_stop(self, stop: Arc<AtomicBool>) -> Search<B> {
+        Search {
+            stop: Some(stop),
+            ..self
+        }
+    }
+
+    pub fn stopped(&self) -> bool {
+        self.stop.as_ref().is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+
+    // Look-ahead safety of one of our moves from the root
+    pub fn safety(&self, move_dir: Move, depth: i32, table: &TranspositionTable) -> f64 {
+        self.safety_from(&self.root, move_dir, depth, table)
+    }
+
+    // The same from any position the search reached, as if it were the root
+    pub fn safety_from(&self, position: &Position<B>, move_dir: Move, depth: i32, table: &TranspositionTable) -> f64 {
+        let mut position = position.clone();
+        let predictions = self.predict(&position);
+        self.evaluate(&mut position, &predictions, move_dir, depth, table)
+    }
+
+    // The `limit` likeliest ways the opponents could move together, likeliest first, each with its
+    // chance and predictions certain of it for `make`. Keeping only the likeliest combinations after each
+    // snake still finds the likeliest overall, since each extends a combination kept.
+    pub fn likely_replies(&self, position: &Position<B>, predictions: &Predictions, limit: usize) -> Vec<(f64, Predictions)> {
+        let mut replies = vec![(1.0, Predictions::default())];
+        for (i, snake) in position.snakes.iter().enumerate() {
+            let moves: Vec<(u16, f64)> = predictions.0[i].moves().collect();
+            if snake.index == 0 || moves.is_empty() {
+                continue;
+            }
+
+            replies = replies
+                .iter()
+                .flat_map(|(chance, reply)| {
+                    moves.iter().map(move |&(square, probability)| {
+                        let mut reply = *reply;
+                        reply.0[i] = Distribution::certain(square);
+                        (chance * probability, reply)
+                    })
+                })
+                .collect();
+            replies.sort_by(|a, b| b.0.total_cmp(&a.0));
+            replies.truncate(limit);
+        }
+        replies
+    }
+
+    // Where every opponent in a position might go, worked out once for the position rather than once
+    // for each of our moves from it. Indexed like `position.snakes`, with nothing for us.
+    pub fn predict(&self, position: &Position<B>) -> Predictions {
+        let mut heads = [(0, 0); MAX_SNAKES];
+        for (head, snake) in heads.iter_mut().zip(&position.snakes) {
+            *head = self.grid.coords(snake.head());
+        }
+        let heads = &heads[..position.snakes.len()];
+
+        let mut predictions = Predictions::default();
+        for (i, snake) in position.snakes.iter().enumerate() {
+            if snake.index != 0 {
+                predictions.0[i] = self.move_probabilities(position, i, heads);
+            }
+        }
+        predictions
+    }
+
+    // The directions, as `Towards`, that bring a head closer to some food than it is to the nearest one.
+    // A single step only gains one square, so those are the steps towards any of the nearest.
+    fn towards_food(&self, position: &Position<B>, head: (i32, i32)) -> Towards {
+        let mut nearest = i32::MAX;
+        let mut towards = Towards::default();
+        for food in position.food.squares() {
+            let (x, y) = self.grid.coords(food);
+            let (dx, dy) = (x - head.0, y - head.1);
+            let distance = dx.abs() + dy.abs();
+            if distance > nearest {
+                continue;
+            }
+            if distance < nearest {
+                nearest = distance;
+                towards = Towards::default();
+            }
+            towards.add(dx, dy);
+        }
+        towards
+    }
+
+    // The plausible moves of the `i`th snake, the ones that don't run off the board or into a body as in
+    // `opponent::plausible_moves`, with the probability of each. `heads` holds every snake's head.
+    fn move_probabilities(&self, position: &Position<B>, i: usize, heads: &[(i32, i32)]) -> Distribution {
+        let snake = &position.snakes[i];
+        let head = heads[i];
+        let food = self.towards_food(position, head);
+        let mut tail = Towards::default();
+        if snake.body.len() > 2 {
+            let (x, y) = self.grid.coords(snake.tail());
+            tail.add(x - head.0, y - head.1);
+        }
+
+        let mut distribution = Distribution::default();
+        let mut candidates = [(Move::Up, MoveTraits::default()); 4];
+        let from = snake.head();
+        for move_dir in Move::ALL {
+            let Some(square) = self.grid.neighbour(from, move_dir) else {
+                continue;
+            };
+            if position.blocked(square, |_| true) {
+                continue;
+            }
+            let to = self.grid.coords(square);
+            let (dx, dy) = (to.0 - head.0, to.1 - head.1);
+            let head_to_head = heads
+                .iter()
+                .enumerate()
+                .any(|(j, &(x, y))| j != i && (x - to.0).abs() + (y - to.1).abs() <= 1);
+            let traits = MoveTraits {
+                food: food.contains(dx, dy),
+                wall: self.grid.on_wall(square),
+                head_to_head,
+                tail: tail.contains(dx, dy),
+            };
+            candidates[distribution.len] = (move_dir, traits);
+            distribution.squares[distribution.len] = square;
+            distribution.len += 1;
+        }
+
+        self.predictors[snake.index].weigh(&candidates[..distribution.len], &mut distribution.probabilities);
+        distribution
+    }
+
+    fn head_to_head_risk(&self, position: &Position<B>, predictions: &Predictions, square: u16, us: &Snake) -> f64 {
+        let mut survival = 1.0;
+
+        for (snake, distribution) in position.snakes.iter().zip(&predictions.0) {
+            if snake.index == us.index || snake.length < us.length || self.grid.distance(snake.head(), square) != 1 {
+                continue;
+            }
+
+            let probability: f64 = distribution.moves().filter(|(to, _)| *to == square).map(|(_, p)| p).sum();
+            survival *= 1.0 - probability;
+        }
+
+        1.0 - survival
+    }
+
+    // One turn: we make `move_dir`, every opponent its most likely move, and whoever loses a head-to-head
+    // is removed, as in `simulation::advance`. `predictions` are for `position`.
+    pub fn advance(&self, position: &Position<B>, predictions: &Predictions, move_dir: Move) -> Position<B> {
+        let mut next = position.clone();
+        self.make(&mut next, predictions, move_dir);
+        next
+    }
+
+    // `advance` in place, returning what `unmake` needs to take it back. We have to be alive in `position`.
+    pub fn make(&self, position: &mut Position<B>, predictions: &Predictions, move_dir: Move) -> Undo<B> {
+        let mut undo = Undo {
+            moved: [None; MAX_SNAKES],
+            removed: Vec::new(),
+            tails: position.tails.clone(),
+            key: position.key,
+        };
+
+        // Snakes move in board order, so whoever's first gets to food first.
+        // A snake with nowhere to go is trapped, and comes off the board.
+        let mut stays = 0u32;
+        for (i, snake) in position.snakes.iter_mut().enumerate() {
+            let new_head = if i == 0 {
+                self.grid.neighbour(snake.head(), move_dir)
+            } else {
+                predictions.0[i].most_likely()
+            };
+            let Some(new_head) = new_head else {
+                continue;
+            };
+
+            let mut step = Step {
+                health: snake.health,
+                length: snake.length,
+                ate: false,
+                tail: None,
+            };
+            let (from, to) = (self.grid.coords(snake.head()), self.grid.coords(new_head));
+            position.key ^= transposition::head(snake.id, from) ^ transposition::head(snake.id, to);
+            position.key ^= transposition::link(snake.id, to, from);
+
+            snake.health -= 1;
+            snake.body.push_front(new_head);
+            position.occupied.add(new_head);
+            if position.food.get(new_head) {
+                position.food.unset(new_head);
+                position.key ^= transposition::food(to);
+                position.key ^= transposition::length(snake.id, snake.length);
+                position.key ^= transposition::length(snake.id, snake.length + 1);
+                snake.health = 100;
+                snake.length += 1;
+                step.ate = true;
+            } else if snake.body.len() > snake.length as usize {
+                let tail = snake.body.pop_back().unwrap();
+                position.occupied.remove(tail);
+                position.key ^= transposition::link(snake.id, self.grid.coords(snake.tail()), self.grid.coords(tail));
+                step.tail = Some(tail);
+            }
+            undo.moved[i] = Some(step);
+            stays |= 1 << i;
+        }
+
+        // Shorter snakes lose head-to-heads
+        let snakes = &position.snakes;
+        for (i, snake) in snakes.iter().enumerate() {
+            let beaten = (0..snakes.len())
+                .any(|j| stays & (1 << j) != 0 && snakes[j].head() == snake.head() && snakes[j].length > snake.length);
+            if beaten {
+                stays &= !(1 << i);
+            }
+        }
+
+        for i in (0..position.snakes.len()).rev() {
+            if stays & (1 << i) == 0 {
+                let snake = position.snakes.remove(i);
+                snake.body.iter().for_each(|square| position.occupied.remove(*square));
+                position.key ^= self.grid.snake_key(&snake);
+                undo.removed.push((i, snake));
+            }
+        }
+
+        position.rebuild_tails(&self.grid);
+        undo
+    }
+
+    pub fn unmake(&self, position: &mut Position<B>, undo: Undo<B>) {
+        for (i, snake) in undo.removed.into_iter().rev() {
+            snake.body.iter().for_each(|square| position.occupied.add(*square));
+            position.snakes.insert(i, snake);
+        }
+
+        for (snake, step) in position.snakes.iter_mut().zip(undo.moved) {
+            let Some(step) = step else {
+                continue;
+            };
+            let head = snake.body.pop_front().unwrap();
+            position.occupied.remove(head);
+            if let Some(tail) = step.tail {
+                snake.body.push_back(tail);
+                position.occupied.add(tail);
+            }
+            if step.ate {
+                position.food.set(head);
+            }
+            snake.health = step.health;
+            snake.length = step.length;
+        }
+
+        position.tails = undo.tails;
+        position.key = undo.key;
+    }
+
+    // Look ahead `depth` moves, as `logic::evaluate_move_safety` does. `position` is back as it was afterwards.
+    fn evaluate(
+        &self,
+        position: &mut Position<B>,
+        predictions: &Predictions,
+        move_dir: Move,
+        depth: i32,
+        table: &TranspositionTable,
+    ) -> f64 {
+        if depth == 0 {
+            return 1.0;
+        }
+        if self.stopped() {
+            return 0.0;
+        }
+
+        let Some(us) = position.us() else {
+            return 0.0;
+        };
+        let Some(new_head) = self.grid.neighbour(us.head(), move_dir) else {
+            return 0.0;
+        };
+        if position.occupied.get(new_head) {
+            return 0.0;
+        }
+
+        let head_to_head_risk = self.head_to_head_risk(position, predictions, new_head, us);
+        if head_to_head_risk >= self.risk_limit {
+            return 0.0;
+        }
+
+        let undo = self.make(position, predictions, move_dir);
+        let total_safety = self.remembered_replies(position, depth, table);
+        self.unmake(position, undo);
+
+        total_safety * (1.0 - head_to_head_risk)
+    }
+
+    // `evaluate_replies`, through the table. The last move is quicker to count than to look up.
+    fn remembered_replies(&self, position: &mut Position<B>, depth: i32, table: &TranspositionTable) -> f64 {
+        if depth == 1 {
+            return self.evaluate_replies(position, depth, table);
+        }
+        if let Some(total_safety) = table.get(position.key, depth) {
+            return total_safety;
+        }
+
+        let total_safety = self.evaluate_replies(position, depth, table);
+        if !self.stopped() {
+            table.store(position.key, depth, total_safety);
+        }
+        total_safety
+    }
+
+    // The summed safety of every move we could make next, 0 if there's none or we're dead
+    fn evaluate_replies(&self, position: &mut Position<B>, depth: i32, table: &TranspositionTable) -> f64 {
+        let Some(us) = position.us() else {
+            return 0.0;
+        };
+        let our_head = us.head();
+
+        // The last move of the look-ahead counts as safe whatever opponents do, so don't predict them for it
+        let mut predictions = None;
+        let mut total_safety = 0.0;
+
+        for next_move in Move::ALL {
+            let Some(next_square) = self.grid.neighbour(our_head, next_move) else {
+                continue;
+            };
+            // Tails move out of the way, unless their snake is on food
+            if position.blocked(next_square, |snake| !position.food.get(snake.head())) {
+                continue;
+            }
+
+            if depth == 1 {
+                total_safety += 1.0;
+                continue;
+            }
+            let predictions: &Predictions = predictions.get_or_insert_with(|| self.predict(position));
+            total_safety += self.evaluate(position, predictions, next_move, depth - 1, table);
+        }
+
+        total_safety
+    }
+}
+
+// What a snake was before `make` moved it
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    health: i32,
+    length: i32,
+    ate: bool,
+    tail: Option<u16>,
+}
+
+// Everything `make` changed
+pub struct Undo<B: Bits> {
+    // Indexed like the position's snakes before any came off, None for a snake that was trapped
+    moved: [Option<Step>; MAX_SNAKES],
+    // Snakes taken off the board with where they were, last first
+    removed: Vec<(usize, Snake)>,
+    tails: B,
+    key: Key,
+}
+
+// Look-ahead safety of a move, on the narrowest bitset that holds the board.
+// None when the board can't be represented, see `Search::new`.
+pub fn move_safety(
+    board: &Board,
+    you: &Battlesnake,
+    move_dir: Move,
+    depth: i32,
+    risk_limit: f64,
+    opponents: &OpponentModel,
+    table: &TranspositionTable,
+) -> Option<f64> {
+    if board.width.max(0) * board.height.max(0) <= 128 {
+        Search::<u128>::new(board, you, opponents, risk_limit).map(|search| search.safety(move_dir, depth, table))
+    } else {
+        Search::<WideBits>::new(board, you, opponents, risk_limit).map(|search| search.safety(move_dir, depth, table))
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// Minimal `--name value` / `--name=value` / `--flag` argument parsing for the offline tools
+
+#[derive(Debug, Default)]
+pub struct Args {
+    values: HashMap<String, String>,
+    flags: Vec<String>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    pub fn from_env() -> Args {
+        Args::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Args {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter().peekable();
+
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                parsed.positional.push(arg);
+                continue;
+            };
+
+            if let Some((name, value)) = name.split_once('=') {
+                parsed.values.insert(name.to_string(), value.to_string());
+            } else if args.peek().is_some_and(|next| !next.starts_with("--")) {
+                parsed.values.insert(name.to_string(), args.next().unwrap());
+            } else {
+                parsed.flags.push(name.to_string());
+            }
+        }
+
+        parsed
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+
+    pub fn get_opt<T: FromStr>(&self, name: &str) -> Result<Option<T>, String>
+    where
+        T::Err: std::fmt::Display,
+    {
+        match self.values.get(name) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|e| format!("invalid --{} {}: {}", name, value, e)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get<T: FromStr>(&self, name: &str, default: T) -> Result<T, String>
+    where
+        T::Err: std::fmt::Display,
+    {
+        Ok(self.get_opt(name)?.unwrap_or(default))
+    }
+
+    // A comma separated list, e.g. `--snakes rusty,cautious`
+    pub fn list(&self, name: &str, default: &str) -> Vec<String> {
+        self.values
+            .get(name)
+            .map_or(default, String::as_str)
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
@@ -0,0 +1,173 @@
+// Static evaluation: how likely a snake is to win from a board, judged from the board alone.
+//
+// The board is boiled down to a handful of features, each scaled to roughly 0 to 1, and the chance of
+// winning is the logistic function of their weighted sum. The weights are part of the profile; the
+// built-in ones are hand-set starting points for `battlesnake-rusty-tune texel` to fit to real games.
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::opponent::OpponentModel;
+use crate::simulation;
+use crate::{Battlesnake, Board, Move};
+
+pub const FEATURES: usize = 6;
+pub const FEATURE_NAMES: [&str; FEATURES] = ["length", "health", "space", "food_distance", "opponents", "centre"];
+
+// Anything that can judge a board, to choose between the moves the search finds safe and for /explain.
+// The search itself only counts the lines we survive, and doesn't ask an evaluator about its leaves.
+pub trait Evaluator: Send + Sync {
+    // Chance that `you` goes on to win from `board`, from 0 to 1
+    fn evaluate(&self, board: &Board, you: &Battlesnake) -> f64;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Evaluation {
+    pub bias: f64,
+    // Our length over the longest opponent's, in tens of segments
+    pub length: f64,
+    // Our health, out of 100
+    pub health: f64,
+    // The share of the board our head can reach
+    pub space: f64,
+    // How far the nearest food is, over the width plus the height, 1 without any
+    pub food_distance: f64,
+    // The share of the snakes left that are opponents, 0 once we're alone
+    pub opponents: f64,
+    // How far our head is from the centre, 1 in a corner
+    pub centre: f64,
+}
+
+impl Default for Evaluation {
+    fn default() -> Evaluation {
+        Evaluation {
+            bias: 0.0,
+            length: 1.0,
+            health: 0.5,
+            space: 2.0,
+            food_distance: -0.5,
+            opponents: -1.6,
+            centre: -0.5,
+        }
+    }
+}
+
+impl Evaluation {
+    // The bias, then a weight for each feature in the order of FEATURE_NAMES
+    pub fn coefficients(&self) -> [f64; FEATURES + 1] {
+        [
+            self.bias,
+            self.length,
+            self.health,
+            self.space,
+            self.food_distance,
+            self.opponents,
+            self.centre,
+        ]
+    }
+
+    pub fn from_coefficients(c: [f64; FEATURES + 1]) -> Evaluation {
+        Evaluation {
+            bias: c[0],
+            length: c[1],
+            health: c[2],
+            space: c[3],
+            food_distance: c[4],
+            opponents: c[5],
+            centre: c[6],
+        }
+    }
+
+    pub fn win_chance(&self, features: &[f64; FEATURES]) -> f64 {
+        let c = self.coefficients();
+        let score = c[0] + c[1..].iter().zip(features).map(|(weight, feature)| weight * feature).sum::<f64>();
+        logistic(score)
+    }
+}
+
+impl Evaluator for Evaluation {
+    fn evaluate(&self, board: &Board, you: &Battlesnake) -> f64 {
+        self.win_chance(&features(board, you))
+    }
+}
+
+// The chance of winning once each of `moves` is played out for a turn, with every opponent making its
+// likeliest move. 0 for a move we don't survive.
+pub fn after_moves(
+    board: &Board,
+    you: &Battlesnake,
+    moves: &[Move],
+    opponents: &OpponentModel,
+    evaluator: &dyn Evaluator,
+) -> HashMap<Move, f64> {
+    moves
+        .iter()
+        .map(|move_dir| {
+            let next = simulation::advance(board, you, move_dir, opponents);
+            let chance = next
+                .snakes
+                .iter()
+                .find(|snake| snake.id == you.id)
+                .map_or(0.0, |us| evaluator.evaluate(&next, us));
+            (*move_dir, chance)
+        })
+        .collect()
+}
+
+pub fn logistic(score: f64) -> f64 {
+    1.0 / (1.0 + (-score).exp())
+}
+
+// The features of `board` for `you`, in the order of FEATURE_NAMES
+pub fn features(board: &Board, you: &Battlesnake) -> [f64; FEATURES] {
+    let (width, height) = (board.width.max(1), board.height.max(1));
+    let opponents: Vec<&Battlesnake> = board.snakes.iter().filter(|s| s.id != you.id).collect();
+
+    let longest = opponents.iter().map(|s| s.length).max().unwrap_or(0);
+    let nearest_food = board
+        .food
+        .iter()
+        .map(|f| (f.x - you.head.x).abs() + (f.y - you.head.y).abs())
+        .min()
+        .map_or(1.0, |distance| distance as f64 / (width + height) as f64);
+    let (centre_x, centre_y) = ((width - 1) as f64 / 2.0, (height - 1) as f64 / 2.0);
+    let from_centre = (you.head.x as f64 - centre_x).abs() + (you.head.y as f64 - centre_y).abs();
+
+    [
+        (you.length - longest) as f64 / 10.0,
+        you.health as f64 / 100.0,
+        reachable(board, you) as f64 / (width * height) as f64,
+        nearest_food,
+        opponents.len() as f64 / board.snakes.len().max(1) as f64,
+        if centre_x + centre_y > 0.0 { from_centre / (centre_x + centre_y) } else { 0.0 },
+    ]
+}
+
+// How many squares `you` could get to from its head, going round every snake's body
+fn reachable(board: &Board, you: &Battlesnake) -> usize {
+    let (width, height) = (board.width.max(0), board.height.max(0));
+    let index = |x: i32, y: i32| (y * width + x) as usize;
+    let on_board = |x: i32, y: i32| x >= 0 && x < width && y >= 0 && y < height;
+
+    let mut blocked = vec![false; (width * height) as usize];
+    for segment in board.snakes.iter().flat_map(|s| &s.body) {
+        if on_board(segment.x, segment.y) {
+            blocked[index(segment.x, segment.y)] = true;
+        }
+    }
+
+    let mut queue = VecDeque::from([(you.head.x, you.head.y)]);
+    let mut count = 0;
+    while let Some((x, y)) = queue.pop_front() {
+        for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+            if on_board(nx, ny) && !blocked[index(nx, ny)] {
+                blocked[index(nx, ny)] = true;
+                count += 1;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    count
+}
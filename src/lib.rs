@@ -0,0 +1,32 @@
+//! Rusty's game types, simulation and strategies.
+//!
+//! The Rocket server in `main.rs` is one consumer of this library; offline tools can depend on
+//! exactly the same code the server runs.
+
+pub mod api;
+pub mod arena;
+pub mod ascii;
+pub mod bitboard;
+pub mod cli;
+pub mod evaluation;
+pub mod logic;
+#[cfg(feature = "neural")]
+pub mod neural;
+pub mod opponent;
+pub mod ponder;
+pub mod profiles;
+pub mod recorder;
+pub mod remote;
+pub mod replay;
+pub mod rules;
+pub mod scheduler;
+pub mod session;
+pub mod simulation;
+pub mod spsa;
+pub mod strategy;
+pub mod texel;
+pub mod tournament;
+pub mod transposition;
+pub mod weights;
+
+pub use api::{Battlesnake, Board, Coord, Game, GameState, Move};
@@ -1,10 +1,24 @@
 use log::info;
+use serde::Serialize;
 use serde_json::{json, Value};
 use std::collections::{HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 use rand::prelude::IndexedRandom;
 
+use crate::bitboard;
+use crate::opponent::OpponentModel;
+use crate::rules::Settings;
+use crate::session::GameSession;
+use crate::simulation::{self, coords_equal, simulate_move};
+use crate::transposition::{self, TranspositionTable};
+use crate::weights::Weights;
 use crate::{Battlesnake, Board, Coord, Game, Move};
 
+// How many moves the look-ahead searches.
+// Seems like the sweet spot is 5-8 before it starts to rule out too many moves
+pub const LOOK_AHEAD_DEPTH: i32 = 8;
+
 pub fn info() -> Value {
     json!({
         "apiversion": "1",
@@ -25,10 +39,6 @@ pub fn end(_game: &Game, _turn: &i32, _board: &Board, _you: &Battlesnake) {
     info!("GAME OVER");
 }
 
-fn coords_equal(a: &Coord, b: &Coord) -> bool {
-    a.x == b.x && a.y == b.y
-}
-
 fn is_position_safe(pos: &Coord, board: &Board, you: &Battlesnake, look_ahead: bool) -> bool {
     // Check if the position is out of bounds
     if pos.x < 0 || pos.x >= board.width || pos.y < 0 || pos.y >= board.height {
@@ -49,7 +59,8 @@ fn is_position_safe(pos: &Coord, board: &Board, you: &Battlesnake, look_ahead: b
         }
     }
 
-    // Check if position collides with other snakes
+    // Check if position collides with other snakes.
+    // Head-to-head threats are weighed separately by the opponent model during look-ahead.
     for snake in &board.snakes {
         if snake.id == you.id {
             continue; // Skip your own snake, already checked
@@ -65,239 +76,79 @@ fn is_position_safe(pos: &Coord, board: &Board, you: &Battlesnake, look_ahead: b
                 return false;
             }
         }
-
-        // Check for potential head-to-head collisions
-        if look_ahead {
-            // Calculate the distance between the snake's head and the position
-            let distance = (snake.head.x - pos.x).abs() + (snake.head.y - pos.y).abs();
-
-            // If the distance is 1, the snake could move to this position in its next turn
-            if distance == 1 {
-                // If the other snake is longer or equal in length, avoid this position
-                if snake.length >= you.length {
-                    return false;
-                }
-            }
-
-            // If the position is the same as the snake's head, it's a direct collision
-            if snake.head.x == pos.x && snake.head.y == pos.y {
-                // If the other snake is longer or equal in length, avoid this position
-                if snake.length >= you.length {
-                    return false;
-                }
-            }
-        }
     }
 
     true
 }
 
-// Simulate a move and return the new position
-fn simulate_move(head: &Coord, move_dir: &Move) -> Coord {
-    match move_dir {
-        Move::Up => Coord { x: head.x, y: head.y + 1 },
-        Move::Down => Coord { x: head.x, y: head.y - 1 },
-        Move::Left => Coord { x: head.x - 1, y: head.y },
-        Move::Right => Coord { x: head.x + 1, y: head.y },
-    }
-}
-
-// Look ahead multiple moves and evaluate safety
-fn evaluate_move_safety(board: &Board, you: &Battlesnake, move_dir: &Move, depth: i32) -> i32 {
+// Look ahead multiple moves and evaluate safety.
+// Opponents reply with their most likely move, and every step is weighted by the chance
+// that no longer snake takes the square from us head-to-head.
+// The search normally runs on the bitboard instead, this is for the boards it can't hold.
+// Positions already scored at the same depth are looked up in `table` rather than searched again.
+// A square a longer snake moves onto with a chance of `risk_limit` or more counts as unsafe.
+pub fn evaluate_move_safety(
+    board: &Board,
+    you: &Battlesnake,
+    move_dir: &Move,
+    depth: i32,
+    risk_limit: f64,
+    opponents: &OpponentModel,
+    table: &TranspositionTable,
+) -> f64 {
     if depth == 0 {
-        return 1; // Base case: move is safe at this depth
+        return 1.0; // Base case: move is safe at this depth
     }
 
     let new_head = simulate_move(&you.head, move_dir);
 
     // If the immediate move is unsafe, return 0
     if !is_position_safe(&new_head, board, you, true) {
-        return 0;
-    }
-
-    // Create a simulated board for the next move
-    let mut simulated_board = Board {
-        height: board.height,
-        width: board.width,
-        food: board.food.clone(),
-        snakes: Vec::new(),
-        hazards: board.hazards.clone(),
-    };
-
-    // Create a simulated you for the next move
-    let mut simulated_you = Battlesnake {
-        id: you.id.clone(),
-        name: you.name.clone(),
-        health: you.health - 1, // Decrease health by 1 each turn
-        body: you.body.clone(),
-        head: new_head.clone(),
-        length: you.length,
-        latency: you.latency.clone(),
-        shout: you.shout.clone(),
-    };
-
-    // Update the head to the new position
-    simulated_you.head = new_head.clone();
-    simulated_you.body.insert(0, new_head.clone());
-
-    // Check if the snake ate food
-    let mut ate_food = false;
-    for (i, food) in board.food.iter().enumerate() {
-        if coords_equal(&new_head, food) {
-            ate_food = true;
-            // Remove food from the simulated board
-            simulated_board.food = board.food.clone();
-            simulated_board.food.remove(i);
-            // Increase health to 100 when eating food
-            simulated_you.health = 100;
-            // Increase length by 1
-            simulated_you.length += 1;
-            break;
-        }
+        return 0.0;
     }
 
-    // If the snake didn't eat food, remove the tail
-    if !ate_food && simulated_you.body.len() > simulated_you.length as usize {
-        simulated_you.body.pop();
+    // If a longer snake is likely to contest the square, treat it as unsafe
+    let head_to_head_risk = opponents.head_to_head_risk(&new_head, board, you);
+    if head_to_head_risk >= risk_limit {
+        return 0.0;
     }
 
-    // Add the simulated you to the simulated board
-    simulated_board.snakes.push(simulated_you.clone());
-
-    // Simulate other snakes' movements
-    for snake in &board.snakes {
-        if snake.id == you.id {
-            continue; // Skip your own snake, already handled
-        }
-
-        // Simple AI for other snakes: try to move towards food or away from walls
-        let possible_moves = [Move::Up, Move::Down, Move::Left, Move::Right];
-        let mut safe_moves = Vec::new();
-
-        for &move_dir in &possible_moves {
-            let new_head = simulate_move(&snake.head, &move_dir);
-
-            // Skip if the move is out of bounds
-            if new_head.x < 0 || new_head.x >= board.width || new_head.y < 0 || new_head.y >= board.height {
-                continue;
-            }
-
-            // Skip if the move collides with any snake's body (except the tail which will move)
-            let mut collision = false;
-            for other_snake in &board.snakes {
-                for (i, body_part) in other_snake.body.iter().enumerate() {
-                    if i == other_snake.body.len() - 1 && other_snake.body.len() > 1 {
-                        // Skip the tail
-                        continue;
-                    }
-                    if body_part.x == new_head.x && body_part.y == new_head.y {
-                        collision = true;
-                        break;
-                    }
-                }
-                if collision {
-                    break;
-                }
-            }
-
-            if !collision {
-                safe_moves.push(move_dir);
-            }
-        }
-
-        // If there are no safe moves, the snake is trapped
-        if safe_moves.is_empty() {
-            continue;
-        }
+    // Play the turn out: our move, the opponents' most likely replies, and any head-to-heads
+    let simulated_board = simulation::advance(board, you, move_dir, opponents);
 
-        // Choose a move (for simplicity, just take the first safe move)
-        let chosen_move = safe_moves[0];
-        let new_head = simulate_move(&snake.head, &chosen_move);
-
-        // Create a simulated snake for the next move
-        let mut simulated_snake = Battlesnake {
-            id: snake.id.clone(),
-            name: snake.name.clone(),
-            health: snake.health - 1, // Decrease health by 1 each turn
-            body: snake.body.clone(),
-            head: new_head.clone(),
-            length: snake.length,
-            latency: snake.latency.clone(),
-            shout: snake.shout.clone(),
-        };
-
-        // Update the head to the new position
-        simulated_snake.head = new_head.clone();
-        simulated_snake.body.insert(0, new_head.clone());
-
-        // Check if the snake ate food
-        let mut ate_food = false;
-        for (i, food) in simulated_board.food.iter().enumerate() {
-            if coords_equal(&new_head, food) {
-                ate_food = true;
-                // Remove food from the simulated board
-                simulated_board.food.remove(i);
-                // Increase health to 100 when eating food
-                simulated_snake.health = 100;
-                // Increase length by 1
-                simulated_snake.length += 1;
-                break;
-            }
-        }
-
-        // If the snake didn't eat food, remove the tail
-        if !ate_food && simulated_snake.body.len() > simulated_snake.length as usize {
-            simulated_snake.body.pop();
-        }
-
-        // Add the simulated snake to the simulated board
-        simulated_board.snakes.push(simulated_snake);
-    }
-
-    // Check for head-to-head collisions
-    let mut head_positions = HashMap::new();
-    for snake in &simulated_board.snakes {
-        let entry = head_positions.entry((snake.head.x, snake.head.y)).or_insert_with(Vec::new);
-        entry.push(snake.clone());
-    }
-
-    // Remove snakes that lost head-to-head collisions
-    let mut snakes_to_remove = Vec::new();
-    for (_, colliding_snakes) in &head_positions {
-        if colliding_snakes.len() > 1 {
-            // Find the longest snake
-            let mut max_length = 0;
-            for snake in colliding_snakes {
-                if snake.length > max_length {
-                    max_length = snake.length;
-                }
-            }
+    // Check if our snake is still alive
+    let Some(our_snake) = simulated_board.snakes.iter().find(|snake| snake.id == you.id) else {
+        return 0.0; // Our snake died
+    };
 
-            // Mark shorter snakes for removal
-            for snake in colliding_snakes {
-                if snake.length < max_length {
-                    snakes_to_remove.push(snake.id.clone());
-                }
-            }
-        }
+    // The last move is quicker to count than to look up
+    if depth == 1 {
+        return evaluate_replies(&simulated_board, our_snake, depth, risk_limit, opponents, table) * (1.0 - head_to_head_risk);
     }
+    let key = transposition::board_key(&simulated_board);
+    let total_safety = table.get(key, depth).unwrap_or_else(|| {
+        let total_safety = evaluate_replies(&simulated_board, our_snake, depth, risk_limit, opponents, table);
+        table.store(key, depth, total_safety);
+        total_safety
+    });
 
-    // Remove the marked snakes
-    simulated_board.snakes.retain(|snake| !snakes_to_remove.contains(&snake.id));
-
-    // Check if our snake is still alive
-    let our_snake = simulated_board.snakes.iter().find(|snake| snake.id == you.id);
-    if our_snake.is_none() {
-        return 0; // Our snake died
-    }
+    total_safety * (1.0 - head_to_head_risk)
+}
 
+// The summed safety of every move we could make next, 0 if there's none
+fn evaluate_replies(
+    board: &Board,
+    you: &Battlesnake,
+    depth: i32,
+    risk_limit: f64,
+    opponents: &OpponentModel,
+    table: &TranspositionTable,
+) -> f64 {
     // For each possible next move, recursively evaluate
-    let possible_moves = [Move::Up, Move::Down, Move::Left, Move::Right];
-    let mut safe_next_moves = 0;
-    let mut total_safety = 0;
+    let mut total_safety = 0.0;
 
-    for &next_move in &possible_moves {
-        let next_pos = simulate_move(&our_snake.unwrap().head, &next_move);
+    for next_move in Move::ALL {
+        let next_pos = simulate_move(&you.head, &next_move);
 
         // Skip if the move is out of bounds
         if next_pos.x < 0 || next_pos.x >= board.width || next_pos.y < 0 || next_pos.y >= board.height {
@@ -306,12 +157,12 @@ fn evaluate_move_safety(board: &Board, you: &Battlesnake, move_dir: &Move, depth
 
         // Skip if the move collides with any snake's body (except the tail which will move)
         let mut collision = false;
-        for snake in &simulated_board.snakes {
+        for snake in &board.snakes {
             for (i, body_part) in snake.body.iter().enumerate() {
                 if i == snake.body.len() - 1 && snake.body.len() > 1 {
                     // Skip the tail if it will move
                     let mut tail_will_move = true;
-                    for food in &simulated_board.food {
+                    for food in &board.food {
                         if coords_equal(&snake.head, food) {
                             tail_will_move = false;
                             break;
@@ -332,20 +183,296 @@ fn evaluate_move_safety(board: &Board, you: &Battlesnake, move_dir: &Move, depth
         }
 
         if !collision {
-            safe_next_moves += 1;
-            total_safety += evaluate_move_safety(&simulated_board, our_snake.unwrap(), &next_move, depth - 1);
+            total_safety += evaluate_move_safety(board, you, &next_move, depth - 1, risk_limit, opponents, table);
         }
     }
 
-    // If there are no safe next moves, this is a dead end
-    if safe_next_moves == 0 {
-        return 0;
+    total_safety
+}
+
+// What a last-resort move gambles on, in the order we'd rather take the gamble when the odds are equal
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum LastResort {
+    Open,
+    HeadToHead,
+    Tail,
+    Hazard,
+    Fatal,
+}
+
+// The ruleset's hazard damage, or the engine's default when it doesn't say
+fn hazard_damage(game: &Game) -> i32 {
+    game.ruleset
+        .get("settings")
+        .and_then(|settings| settings.get("hazardDamagePerTurn"))
+        .and_then(Value::as_i64)
+        .map_or(Settings::default().hazard_damage_per_turn, |damage| damage as i32)
+}
+
+// Probability that a snake moves its head onto one of `targets` next turn
+fn chance_of_reaching(snake: &Battlesnake, board: &Board, opponents: &OpponentModel, targets: &[Coord]) -> f64 {
+    opponents
+        .move_probabilities(snake, board)
+        .iter()
+        .filter(|(move_dir, _)| targets.contains(&simulate_move(&snake.head, move_dir)))
+        .map(|(_, p)| p)
+        .sum::<f64>()
+        .min(1.0)
+}
+
+// Probability that we're still alive after moving onto `pos` this turn, given what we know about opponents.
+// The first gamble the square involves is what it gets ranked as when the odds are tied.
+fn last_resort_survival(
+    pos: &Coord,
+    game: &Game,
+    board: &Board,
+    you: &Battlesnake,
+    opponents: &OpponentModel,
+) -> (LastResort, f64) {
+    if pos.x < 0 || pos.x >= board.width || pos.y < 0 || pos.y >= board.height {
+        return (LastResort::Fatal, 0.0);
     }
 
-    total_safety
+    let mut kind = LastResort::Open;
+    let mut survival = 1.0;
+
+    // Head-to-heads: we only die if a snake at least as long as us actually takes the square
+    for snake in &board.snakes {
+        if snake.id == you.id || (snake.head.x - pos.x).abs() + (snake.head.y - pos.y).abs() != 1 {
+            continue;
+        }
+
+        let probability = chance_of_reaching(snake, board, opponents, std::slice::from_ref(pos));
+        if probability > 0.0 {
+            kind = LastResort::HeadToHead;
+            if snake.length >= you.length {
+                survival *= 1.0 - probability;
+            }
+        }
+    }
+
+    // Bodies: only a tail can get out of the way, and not one that's stacked because its snake just ate
+    for snake in &board.snakes {
+        let body = if snake.id == you.id { &you.body } else { &snake.body };
+        let Some(i) = body.iter().position(|body_part| coords_equal(body_part, pos)) else {
+            continue;
+        };
+
+        let tail = body.len() - 1;
+        if i != tail || body.len() < 2 || coords_equal(&body[tail], &body[tail - 1]) {
+            return (LastResort::Fatal, 0.0);
+        }
+
+        // The tail stays put if its snake eats this turn
+        let eat_probability = if snake.id == you.id {
+            0.0
+        } else {
+            chance_of_reaching(snake, board, opponents, &board.food)
+        };
+        if kind == LastResort::Open {
+            kind = LastResort::Tail;
+        }
+        survival *= 1.0 - eat_probability;
+    }
+
+    let hazard_hits = board.hazards.iter().filter(|hazard| coords_equal(hazard, pos)).count() as i32;
+    if hazard_hits > 0 {
+        if kind == LastResort::Open {
+            kind = LastResort::Hazard;
+        }
+        if you.health - 1 - hazard_damage(game) * hazard_hits <= 0 {
+            survival = 0.0;
+        }
+    }
+
+    (kind, survival)
+}
+
+// When no move is safe, rank every move by the chance we survive one more turn
+pub fn rank_last_resort_moves(game: &Game, board: &Board, you: &Battlesnake, opponents: &OpponentModel) -> Vec<(Move, LastResort, f64)> {
+    let mut ranked: Vec<(Move, LastResort, f64)> = Move::ALL
+        .iter()
+        .map(|move_dir| {
+            let (kind, survival) = last_resort_survival(&simulate_move(&you.head, move_dir), game, board, you, opponents);
+            (*move_dir, kind, survival)
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.1.cmp(&b.1)));
+    ranked
+}
+
+// Look-ahead safety score for one move, zero if it doesn't survive
+pub fn move_safety(
+    board: &Board,
+    you: &Battlesnake,
+    move_dir: Move,
+    depth: i32,
+    risk_limit: f64,
+    opponents: &OpponentModel,
+    table: &TranspositionTable,
+) -> f64 {
+    bitboard::move_safety(board, you, move_dir, depth, risk_limit, opponents, table)
+        .unwrap_or_else(|| evaluate_move_safety(board, you, &move_dir, depth, risk_limit, opponents, table))
+}
+
+// Look-ahead safety score for each of `moves`, zero for the ones that don't survive.
+// Given more than one thread, the moves are shared out between them, each taking the next move
+// nobody has searched yet. They all share the table, so the scores don't depend on how many there are.
+#[allow(clippy::too_many_arguments)]
+pub fn move_safety_scores(
+    board: &Board,
+    you: &Battlesnake,
+    moves: &[Move],
+    depth: i32,
+    risk_limit: f64,
+    opponents: &OpponentModel,
+    table: &TranspositionTable,
+    threads: usize,
+) -> HashMap<Move, f64> {
+    let score = |move_dir: Move| (move_dir, move_safety(board, you, move_dir, depth, risk_limit, opponents, table));
+    let threads = threads.min(moves.len());
+    if threads <= 1 {
+        return moves.iter().map(|&move_dir| score(move_dir)).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut scores = Vec::new();
+                    while let Some(&move_dir) = moves.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        scores.push(score(move_dir));
+                    }
+                    scores
+                })
+            })
+            .collect();
+        workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+    })
+}
+
+// The line the look-ahead rates safest after `move_dir`: each of our moves is the one with the best
+// safety score over the depth left, with opponents making their most likely replies as they do in the search
+pub fn principal_variation(
+    board: &Board,
+    you: &Battlesnake,
+    move_dir: Move,
+    depth: i32,
+    risk_limit: f64,
+    opponents: &OpponentModel,
+    table: &TranspositionTable,
+) -> Vec<Move> {
+    let mut line = vec![move_dir];
+    let mut board = board.clone();
+    let mut you = you.clone();
+
+    for remaining in (1..depth).rev() {
+        board = simulation::advance(&board, &you, line.last().unwrap(), opponents);
+        let Some(snake) = board.snakes.iter().find(|snake| snake.id == you.id) else {
+            break;
+        };
+        you = snake.clone();
+
+        let best = move_safety_scores(&board, &you, &Move::ALL, remaining, risk_limit, opponents, table, 1)
+            .into_iter()
+            .filter(|(_, score)| *score > 0.0)
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.as_str().cmp(a.0.as_str())));
+        let Some((next, _)) = best else {
+            break;
+        };
+        line.push(next);
+    }
+    line
+}
+
+// Everything get_move weighed on the way to a move, in the order it weighed it
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Trace {
+    pub is_move_safe: HashMap<Move, bool>,
+    pub is_move_desirable: HashMap<Move, bool>,
+    pub safety_scores: HashMap<Move, f64>,
+    pub is_longest: bool,
+    pub health_is_low: bool,
+    // The static evaluation's chance of us winning from here, only worked out for /explain
+    pub win_chance: Option<f64>,
+    // Food nearest first, and the moves that close in on the nearest
+    pub food_targets: Vec<Coord>,
+    pub food_seeking_moves: Vec<Move>,
+    pub quadrant: Option<QuadrantChoice>,
+    pub last_resort: Vec<LastResortOption>,
+}
+
+// Quadrants are numbered 1 to 4 as get_move counts them
+#[derive(Serialize, Debug, Clone)]
+pub struct QuadrantChoice {
+    pub body_counts: [i32; 4],
+    pub food_counts: [i32; 4],
+    pub chosen: usize,
+    pub moves: Vec<Move>,
+    pub food: Vec<Coord>,
+    pub food_moves: Vec<Move>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LastResortOption {
+    #[serde(rename = "move")]
+    pub move_dir: Move,
+    pub kind: LastResort,
+    pub survival: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Explanation {
+    #[serde(rename = "move")]
+    pub move_dir: Move,
+    pub shout: Option<String>,
+    pub reason: String,
+    #[serde(flatten)]
+    pub trace: Trace,
+}
+
+impl Explanation {
+    // The /move response for this decision
+    pub fn response(&self) -> Value {
+        match &self.shout {
+            Some(shout) => json!({
+                "move": self.move_dir.as_str(),
+                "shout": shout,
+            }),
+            None => json!({
+                "move": self.move_dir.as_str(),
+            }),
+        }
+    }
 }
 
-pub fn get_move(_game: &Game, turn: &i32, board: &Board, you: &Battlesnake) -> Value {
+pub fn get_move(
+    game: &Game,
+    turn: &i32,
+    board: &Board,
+    you: &Battlesnake,
+    session: &mut GameSession,
+    weights: &Weights,
+) -> Value {
+    explain(game, turn, board, you, session, weights).response()
+}
+
+// Decide on a move, keeping the working
+pub fn explain(
+    game: &Game,
+    turn: &i32,
+    board: &Board,
+    you: &Battlesnake,
+    session: &mut GameSession,
+    weights: &Weights,
+) -> Explanation {
+    let opponents = &session.opponents;
+    let depth = session.depth.unwrap_or(weights.look_ahead_depth);
+    let mut trace = Trace::default();
+
     let mut is_move_safe: HashMap<_, _> = vec![
         (Move::Up, true),
         (Move::Down, true),
@@ -364,7 +491,7 @@ pub fn get_move(_game: &Game, turn: &i32, board: &Board, you: &Battlesnake) -> V
     .into_iter()
     .collect();
 
-    let my_head = &you.body[0];
+    let my_head = &you.head;
     let my_body = &you.body;
     let board_width = &board.width;
     let board_height = &board.height;
@@ -379,10 +506,12 @@ pub fn get_move(_game: &Game, turn: &i32, board: &Board, you: &Battlesnake) -> V
     }
 
     // Check if health is getting low
-    let health_is_low = you.health < 30; // Consider health below 30 as low
+    let health_is_low = you.health < weights.low_health;
+    trace.is_longest = is_longest;
+    trace.health_is_low = health_is_low;
 
     //Get count of my body parts in each quadrant
-    let mut my_body_quadrant_count = vec![0, 0, 0, 0];
+    let mut my_body_quadrant_count = [0, 0, 0, 0];
     for body_part_coord in &you.body {
         let body_part_quadrant = match (
             body_part_coord.x < board_width / 2,
@@ -397,7 +526,7 @@ pub fn get_move(_game: &Game, turn: &i32, board: &Board, you: &Battlesnake) -> V
     }
 
     //Get count of my food in each quadrant
-    let mut my_food_quadrant_count = vec![0, 0, 0, 0];
+    let mut my_food_quadrant_count = [0, 0, 0, 0];
     for food in &board.food {
         let food_quadrant = match (food.x < board_width / 2, food.y < board_height / 2) {
             (true, true) => 1,
@@ -414,36 +543,40 @@ pub fn get_move(_game: &Game, turn: &i32, board: &Board, you: &Battlesnake) -> V
         let b_dist = (b.x - my_head.x).abs() + (b.y - my_head.y).abs();
         a_dist.cmp(&b_dist)
     });
+    trace.food_targets = sorted_food.clone();
 
     let chosen: &Move;
+    let last_resort: Move;
     let mut shout: &str = "";
+    let reason: String;
 
-    if my_head.x <= 1 {
-        if my_head.x == 0 {
-            is_move_safe.insert(Move::Left, false);
-        } else {
-            is_move_desirable.insert(Move::Left, false);
-        }
-    } else if my_head.x >= board_width - 2 {
-        if my_head.x >= board_width - 1 {
-            is_move_safe.insert(Move::Right, false);
-        } else {
-            is_move_desirable.insert(Move::Right, false);
-        }
+    // Moving into a wall is fatal, and moving towards one close by is best avoided.
+    // Each wall is checked on its own, as on a narrow board the head can be next to both.
+    if my_head.x == 0 {
+        is_move_safe.insert(Move::Left, false);
+    }
+    if my_head.x >= board_width - 1 {
+        is_move_safe.insert(Move::Right, false);
+    }
+    if my_head.y == 0 {
+        is_move_safe.insert(Move::Down, false);
+    }
+    if my_head.y >= board_height - 1 {
+        is_move_safe.insert(Move::Up, false);
     }
 
-    if my_head.y <= 1 {
-        if my_head.y == 0 {
-            is_move_safe.insert(Move::Down, false);
-        } else {
-            is_move_desirable.insert(Move::Down, false);
-        }
-    } else if my_head.y >= board_height - 2 {
-        if my_head.y == board_height - 1 {
-            is_move_safe.insert(Move::Up, false);
-        } else {
-            is_move_desirable.insert(Move::Up, false);
-        }
+    let wall_buffer = weights.wall_buffer;
+    if my_head.x < wall_buffer {
+        is_move_desirable.insert(Move::Left, false);
+    }
+    if my_head.x >= board_width - wall_buffer {
+        is_move_desirable.insert(Move::Right, false);
+    }
+    if my_head.y < wall_buffer {
+        is_move_desirable.insert(Move::Down, false);
+    }
+    if my_head.y >= board_height - wall_buffer {
+        is_move_desirable.insert(Move::Up, false);
     }
 
     // Prevent your Battlesnake from colliding with itself
@@ -490,28 +623,22 @@ pub fn get_move(_game: &Game, turn: &i32, board: &Board, you: &Battlesnake) -> V
         }
     }
 
-    // Evaluate moves with look-ahead
-    let possible_moves = [Move::Up, Move::Down, Move::Left, Move::Right];
-    let mut move_safety_scores = HashMap::new();
-
-    for &move_dir in &possible_moves {
-        // Skip moves that are already marked as unsafe
-        if !is_move_safe.get(&move_dir).unwrap_or(&false) {
-            continue;
-        }
-
-        // Evaluate the safety of this move looking ahead n moves
-        // Seems like the sweet spot is 5-8 before it starts to rule out too many moves
-        let safety_score = evaluate_move_safety(board, you, &move_dir, 8);
-        move_safety_scores.insert(move_dir, safety_score);
-    }
+    // Evaluate the moves not already marked as unsafe, looking ahead n moves
+    let candidates: Vec<Move> = Move::ALL.into_iter().filter(|move_dir| is_move_safe[move_dir]).collect();
+    let threads = session.threads.unwrap_or(1);
+    let (risk_limit, table) = (weights.head_to_head_risk_limit, &session.transpositions);
+    let move_safety_scores = move_safety_scores(board, you, &candidates, depth, risk_limit, opponents, table, threads);
+    session.scores = move_safety_scores.clone();
+    trace.safety_scores = move_safety_scores.clone();
 
     // Filter out moves with a safety score of 0
     for (move_dir, score) in &move_safety_scores {
-        if *score == 0 {
+        if *score <= 0.0 {
             is_move_safe.insert(*move_dir, false);
         }
     }
+    trace.is_move_safe = is_move_safe.clone();
+    trace.is_move_desirable = is_move_desirable.clone();
 
     // Are there any safe moves left?
     let safe_moves = is_move_safe
@@ -530,20 +657,20 @@ pub fn get_move(_game: &Game, turn: &i32, board: &Board, you: &Battlesnake) -> V
     // Sort safe moves by their safety score (higher is better)
     let mut scored_safe_moves = safe_moves.clone();
     scored_safe_moves.sort_by(|a, b| {
-        let a_score = move_safety_scores.get(a).unwrap_or(&0);
-        let b_score = move_safety_scores.get(b).unwrap_or(&0);
-        b_score.cmp(a_score) // Descending order
+        let a_score = move_safety_scores.get(a).unwrap_or(&0.0);
+        let b_score = move_safety_scores.get(b).unwrap_or(&0.0);
+        b_score.total_cmp(a_score) // Descending order
     });
 
     // If there is more than one safe move, choose a desirable move which is also a safe move
     if safe_moves.len() > 1 {
         let safe_desirable_moves: &[&Move] = &safe_moves
             .iter()
-            .filter(|&m| desirable_moves.contains(&m))
+            .filter(|&m| desirable_moves.contains(m))
             .collect::<Vec<_>>();
 
         // Prioritize moves towards food if we're not the longest snake or health is low
-        if (!is_longest || health_is_low) && sorted_food.len() > 0 && safe_desirable_moves.len() > 0 {
+        if (!is_longest || health_is_low) && !sorted_food.is_empty() && !safe_desirable_moves.is_empty() {
             info!("Prioritizing food: not longest={}, low health={}", !is_longest, health_is_low);
 
             // Find moves that bring us closer to the nearest food
@@ -579,23 +706,31 @@ pub fn get_move(_game: &Game, turn: &i32, board: &Board, you: &Battlesnake) -> V
                     // Return this move immediately
                     chosen = move_;
                     info!("Moving directly to food: {}", chosen.as_str());
-                    return json!({
-                        "move": chosen.as_str(),
-                    });
+                    trace.food_seeking_moves = food_seeking_moves.into_iter().copied().collect();
+                    return Explanation {
+                        move_dir: *chosen,
+                        shout: None,
+                        reason: "moving directly to the nearest food".to_string(),
+                        trace,
+                    };
                 }
             }
 
             // If we have moves that bring us closer to food, choose one randomly
-            if food_seeking_moves.len() > 0 {
+            trace.food_seeking_moves = food_seeking_moves.iter().map(|&&m| m).collect();
+            if !food_seeking_moves.is_empty() {
                 chosen = food_seeking_moves.choose(&mut rand::rng()).unwrap();
                 info!("Moving towards food: {}", chosen.as_str());
-                return json!({
-                    "move": chosen.as_str(),
-                });
+                return Explanation {
+                    move_dir: *chosen,
+                    shout: None,
+                    reason: "moving towards the nearest food".to_string(),
+                    trace,
+                };
             }
         }
 
-        if safe_desirable_moves.len() > 0 {
+        if !safe_desirable_moves.is_empty() {
             // Determine which quadrant has the least body parts
             let mut min_quadrant = 0;
             let mut min_quadrant_count = my_body_quadrant_count[0];
@@ -606,11 +741,11 @@ pub fn get_move(_game: &Game, turn: &i32, board: &Board, you: &Battlesnake) -> V
                     min_quadrant = i;
                     min_quadrant_count = my_body_quadrant_count[i];
                     max_food_count = 0;
-                } else if my_body_quadrant_count[i] == min_quadrant_count {
-                    if my_food_quadrant_count[i] > max_food_count {
-                        min_quadrant = i;
-                        max_food_count = my_food_quadrant_count[i];
-                    }
+                } else if my_body_quadrant_count[i] == min_quadrant_count
+                    && my_food_quadrant_count[i] > max_food_count
+                {
+                    min_quadrant = i;
+                    max_food_count = my_food_quadrant_count[i];
                 }
             }
 
@@ -640,30 +775,22 @@ pub fn get_move(_game: &Game, turn: &i32, board: &Board, you: &Battlesnake) -> V
                     }
                 }
             }
-            if min_quadrant_moves.len() > 0 {
+            if !min_quadrant_moves.is_empty() {
                 // Filter sorted_food to only include food in min_quadrant
                 let mut min_quadrant_food = vec![];
                 for food in sorted_food {
                     match min_quadrant {
-                        1 => {
-                            if food.x < board_width / 2 && food.y < board_height / 2 {
-                                min_quadrant_food.push(food);
-                            }
+                        1 if food.x < board_width / 2 && food.y < board_height / 2 => {
+                            min_quadrant_food.push(food);
                         }
-                        2 => {
-                            if food.x < board_width / 2 && food.y >= board_height / 2 {
-                                min_quadrant_food.push(food);
-                            }
+                        2 if food.x < board_width / 2 && food.y >= board_height / 2 => {
+                            min_quadrant_food.push(food);
                         }
-                        3 => {
-                            if food.x >= board_width / 2 && food.y < board_height / 2 {
-                                min_quadrant_food.push(food);
-                            }
+                        3 if food.x >= board_width / 2 && food.y < board_height / 2 => {
+                            min_quadrant_food.push(food);
                         }
-                        4 => {
-                            if food.x >= board_width / 2 && food.y >= board_height / 2 {
-                                min_quadrant_food.push(food);
-                            }
+                        4 if food.x >= board_width / 2 && food.y >= board_height / 2 => {
+                            min_quadrant_food.push(food);
                         }
                         _ => {}
                     }
@@ -682,7 +809,7 @@ pub fn get_move(_game: &Game, turn: &i32, board: &Board, you: &Battlesnake) -> V
                         x: my_head.x + dx,
                         y: my_head.y + dy,
                     };
-                    if min_quadrant_food.len() > 0 {
+                    if !min_quadrant_food.is_empty() {
                         let food = &min_quadrant_food[0];
                         if new_head.x == food.x && new_head.y == food.y {
                             min_quadrant_food_moves.push(move_);
@@ -690,42 +817,60 @@ pub fn get_move(_game: &Game, turn: &i32, board: &Board, you: &Battlesnake) -> V
                     }
                 }
 
-                if min_quadrant_food_moves.len() > 0 {
+                if !min_quadrant_food_moves.is_empty() {
                     chosen = min_quadrant_food_moves
                         .choose(&mut rand::rng())
                         .unwrap();
+                    reason = format!("heading for food in quadrant {}, which has the least of our body", min_quadrant + 1);
                 } else {
-                    chosen = &min_quadrant_moves.choose(&mut rand::rng()).unwrap();
+                    chosen = min_quadrant_moves.choose(&mut rand::rng()).unwrap();
+                    reason = format!("heading for quadrant {}, which has the least of our body", min_quadrant + 1);
                 }
+                trace.quadrant = Some(QuadrantChoice {
+                    body_counts: my_body_quadrant_count,
+                    food_counts: my_food_quadrant_count,
+                    chosen: min_quadrant + 1,
+                    moves: min_quadrant_moves.iter().map(|&&m| m).collect(),
+                    food: min_quadrant_food.into_iter().map(|food| food.clone()).collect(),
+                    food_moves: min_quadrant_food_moves.iter().map(|&&m| m).collect(),
+                });
             } else {
                 chosen = safe_desirable_moves
                     .choose(&mut rand::rng())
                     .unwrap();
+                reason = "a random safe and desirable move, none heads for the emptiest quadrant".to_string();
             }
         } else {
             // Choose a random move from the safe ones
             chosen = safe_moves.choose(&mut rand::rng()).unwrap();
+            reason = "a random safe move, none is desirable".to_string();
         }
-    } else {
-        if safe_moves.len() == 0 {
+    } else if safe_moves.is_empty() {
+        // Nothing is safe, so take whichever gamble gives us the best odds of another turn
+        let ranked = rank_last_resort_moves(game, board, you, opponents);
+        let (best, kind, survival) = ranked[0];
+        info!("No safe moves, last resort {} ({:?}, {:.0}% survival)", best.as_str(), kind, survival * 100.0);
+        if survival <= 0.0 {
             shout = "The only winning move is not to play...";
-            chosen = &Move::Up;
-        } else {
-            // Choose a random move from the safe ones
-            chosen = safe_moves.choose(&mut rand::rng()).unwrap();
         }
+        reason = format!("no safe moves, the best last resort with {:.0}% survival", survival * 100.0);
+        trace.last_resort = ranked
+            .into_iter()
+            .map(|(move_dir, kind, survival)| LastResortOption { move_dir, kind, survival })
+            .collect();
+        last_resort = best;
+        chosen = &last_resort;
+    } else {
+        // Choose a random move from the safe ones
+        chosen = safe_moves.choose(&mut rand::rng()).unwrap();
+        reason = "the only safe move".to_string();
     }
 
     info!("MOVE {}: {}", turn, chosen.as_str());
-    let response = if shout.is_empty() {
-        json!({
-            "move": chosen.as_str(),
-        })
-    } else {
-        json!({
-            "move": chosen.as_str(),
-            "shout": shout,
-        })
-    };
-    response
+    Explanation {
+        move_dir: *chosen,
+        shout: (!shout.is_empty()).then(|| shout.to_string()),
+        reason,
+        trace,
+    }
 }
@@ -1,113 +1,167 @@
 #[macro_use]
 extern crate rocket;
 
-use log::info;
+use log::{debug, info};
 use rocket::fairing::AdHoc;
 use rocket::http::Status;
-use rocket::serde::{json::Json, Deserialize};
-use serde::Serialize;
+use rocket::serde::json::Json;
+use rocket::{Route, State};
 use serde_json::Value;
-use std::collections::HashMap;
 use std::env;
-
-mod logic;
-
-// API and Response Objects
-// See https://docs.battlesnake.com/api
-
-#[derive(Deserialize, Serialize, Debug)]
-pub struct Game {
-    id: String,
-    ruleset: HashMap<String, Value>,
-    timeout: u32,
-}
-
-#[derive(Deserialize, Serialize, Debug)]
-pub struct Board {
-    height: i32,
-    width: i32,
-    food: Vec<Coord>,
-    snakes: Vec<Battlesnake>,
-    hazards: Vec<Coord>,
-}
-
-#[derive(Deserialize, Serialize, Debug, Clone)]
-pub struct Battlesnake {
-    id: String,
-    name: String,
-    health: i32,
-    body: Vec<Coord>,
-    head: Coord,
-    length: i32,
-    latency: String,
-    shout: Option<String>,
+use std::path::PathBuf;
+use std::time::Instant;
+
+use battlesnake_rusty::{GameState, ascii, ponder, scheduler};
+use battlesnake_rusty::profiles::ProfileStore;
+use battlesnake_rusty::recorder::{Event, Record, Recorder};
+use battlesnake_rusty::session::Sessions;
+use battlesnake_rusty::strategy::{self, Strategies, Strategy};
+
+// Routes are mounted once per registered strategy, so the base they're mounted at always resolves
+fn strategy_for<'a>(strategies: &'a Strategies, route: &Route) -> &'a dyn Strategy {
+    strategies
+        .mounted_at(route.uri.base())
+        .expect("routes are only mounted for registered strategies")
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq, Hash)]
-pub struct Coord {
-    x: i32,
-    y: i32,
-}
-
-#[derive(Deserialize, Serialize, Debug)]
-pub struct GameState {
-    game: Game,
-    turn: i32,
-    board: Board,
-    you: Battlesnake,
-}
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-enum Move {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-impl Move {
-    fn as_str(&self) -> &'static str {
-        match self {
-            Move::Up => "up",
-            Move::Down => "down",
-            Move::Left => "left",
-            Move::Right => "right",
-        }
+// Appends a request and our answer to the game's recording, when recording is on
+fn record(
+    recorder: &Recorder,
+    event: Event,
+    route: &Route,
+    strategies: &Strategies,
+    state: &GameState,
+    started: Instant,
+    fill: impl FnOnce(Record) -> Record,
+) {
+    if !recorder.is_enabled() {
+        return;
     }
+
+    let snake = strategies.name_mounted_at(route.uri.base()).unwrap_or_default();
+    let mut entry = fill(Record::new(event, snake, state));
+    entry.response_ms = started.elapsed().as_secs_f64() * 1000.0;
+    recorder.record(entry);
 }
 
 #[get("/")]
-fn handle_index() -> Json<Value> {
-    Json(logic::info())
+fn handle_index(route: &Route, strategies: &State<Strategies>) -> Json<Value> {
+    Json(strategy_for(strategies, route).info())
 }
 
 #[post("/start", format = "json", data = "<start_req>")]
-fn handle_start(start_req: Json<GameState>) -> Status {
-    logic::start(
-        &start_req.game,
-        &start_req.turn,
-        &start_req.board,
-        &start_req.you,
-    );
+fn handle_start(
+    start_req: Json<GameState>,
+    route: &Route,
+    strategies: &State<Strategies>,
+    sessions: &State<Sessions>,
+    profiles: &State<ProfileStore>,
+    recorder: &State<Recorder>,
+) -> Status {
+    let started = Instant::now();
+    let session = sessions.get(&start_req.game, &start_req.you);
+    let mut session = session.lock().unwrap();
+    profiles.warm(&mut session.opponents, &start_req.board, &start_req.you);
+
+    strategy_for(strategies, route).start(&start_req, &mut session);
+    record(recorder, Event::Start, route, strategies, &start_req, started, |entry| entry);
 
     Status::Ok
 }
 
 #[post("/move", format = "json", data = "<move_req>")]
-fn handle_move(move_req: Json<GameState>) -> Json<Value> {
-    let response = logic::get_move(
-        &move_req.game,
-        &move_req.turn,
-        &move_req.board,
-        &move_req.you,
-    );
+fn handle_move(
+    move_req: Json<GameState>,
+    route: &Route,
+    strategies: &State<Strategies>,
+    sessions: &State<Sessions>,
+    recorder: &State<Recorder>,
+) -> Json<Value> {
+    let started = Instant::now();
+    let session = sessions.get(&move_req.game, &move_req.you);
+    let mut session = session.lock().unwrap();
+    if let Some(pondering) = session.pondering.take() {
+        pondering.stop();
+    }
+    let strategy = strategy_for(strategies, route);
+    let weights = strategy.weights(&move_req.game);
+    session.opponents.set_latency_thresholds(weights.latency);
+    session.opponents.observe(&move_req.game, &move_req.board, &move_req.you);
+    session.scores.clear();
+    debug!("Turn {} as {} sees it:\n{}", move_req.turn, move_req.you.name, ascii::render(&move_req));
+
+    // The busier the server, the shallower every game searches
+    let load = sessions.load();
+    scheduler::plan(&mut session, &move_req.game, load, &weights);
+    if session.depth < Some(weights.look_ahead_depth) {
+        info!(
+            "{} games on {} threads, looking {} moves ahead",
+            load.games,
+            load.threads,
+            session.depth.unwrap_or_default()
+        );
+    }
+
+    let searching = Instant::now();
+    let response = strategy.get_move(&move_req, &mut session);
+    scheduler::record(&mut session, load, searching.elapsed());
+
+    // Keep searching while the opponents reply, unless other games need the threads
+    if load.share() >= 1.0
+        && let Some(move_dir) = strategy::chosen_move(&response)
+    {
+        session.pondering = ponder::start(&move_req, move_dir, &session, weights.head_to_head_risk_limit);
+    }
+    record(recorder, Event::Move, route, strategies, &move_req, started, |entry| Record {
+        scores: session.scores.clone(),
+        ..entry.with_response(&response)
+    });
 
     Json(response)
 }
 
+// The full reasoning behind the move we'd make, without recording it or touching the live game's session
+#[post("/explain", format = "json", data = "<explain_req>")]
+fn handle_explain(
+    explain_req: Json<GameState>,
+    route: &Route,
+    strategies: &State<Strategies>,
+    sessions: &State<Sessions>,
+) -> Json<Value> {
+    let strategy = strategy_for(strategies, route);
+    let mut session = sessions.peek(&explain_req.game, &explain_req.you).unwrap_or_default();
+    session.opponents.set_latency_thresholds(strategy.weights(&explain_req.game).latency);
+    session.opponents.observe(&explain_req.game, &explain_req.board, &explain_req.you);
+    session.scores.clear();
+    session.threads = Some(sessions.threads_per_game());
+
+    Json(strategy.explain(&explain_req, &mut session))
+}
+
 #[post("/end", format = "json", data = "<end_req>")]
-fn handle_end(end_req: Json<GameState>) -> Status {
-    logic::end(&end_req.game, &end_req.turn, &end_req.board, &end_req.you);
+fn handle_end(
+    end_req: Json<GameState>,
+    route: &Route,
+    strategies: &State<Strategies>,
+    sessions: &State<Sessions>,
+    profiles: &State<ProfileStore>,
+    recorder: &State<Recorder>,
+) -> Status {
+    let started = Instant::now();
+    let finished = sessions.finish(&end_req.game, &end_req.you);
+    let session = finished.clone().unwrap_or_default();
+    let mut session = session.lock().unwrap();
+    if let Some(pondering) = session.pondering.take() {
+        pondering.stop();
+    }
+    session.opponents.observe(&end_req.game, &end_req.board, &end_req.you);
+    // A game we have no session for (after a restart, or one we never played) has nothing to learn from
+    if finished.is_some() {
+        profiles.record_game(&session.opponents, &end_req.board, &end_req.you);
+    }
+
+    strategy_for(strategies, route).end(&end_req, &mut session);
+    record(recorder, Event::End, route, strategies, &end_req, started, |entry| entry);
 
     Status::Ok
 }
@@ -129,9 +183,39 @@ fn rocket() -> _ {
 
     env_logger::init();
 
+    // Opponent profiles are kept in a local file between games, `OPPONENT_PROFILES` overrides where
+    let profiles_path = env::var("OPPONENT_PROFILES").unwrap_or_else(|_| "opponent_profiles.json".to_string());
+
+    // Recording every game is opt-in, by naming a directory in `RECORD_DIR`
+    let record_dir = env::var("RECORD_DIR").ok().filter(|dir| !dir.is_empty()).map(PathBuf::from);
+    if let Some(dir) = &record_dir {
+        info!("Recording games to {}", dir.display());
+    }
+
+    // The look-ahead shares `SEARCH_THREADS` threads between the games in progress, by default one per core
+    let search_threads = env::var("SEARCH_THREADS")
+        .ok()
+        .and_then(|threads| threads.parse().ok())
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    info!("Searching on up to {} threads", search_threads);
+
     info!("Starting Battlesnake Server...");
 
-    rocket::build()
+    // The default snake answers at "/", and every snake also answers under its own name, e.g. "/rusty/move"
+    let strategies = strategy::registered();
+    let mut server = rocket::build();
+    for name in strategies.names() {
+        server = server.mount(
+            format!("/{}", name),
+            routes![handle_index, handle_start, handle_move, handle_explain, handle_end],
+        );
+    }
+
+    server
+        .manage(strategies)
+        .manage(Sessions::with_threads(search_threads))
+        .manage(ProfileStore::load(profiles_path))
+        .manage(Recorder::new(record_dir))
         .attach(AdHoc::on_response("Server ID Middleware", |_, res| {
             Box::pin(async move {
                 res.set_raw_header("Server", "mishagp/github/battlesnake-rusty");
@@ -139,6 +223,6 @@ fn rocket() -> _ {
         }))
         .mount(
             "/",
-            routes![handle_index, handle_start, handle_move, handle_end],
+            routes![handle_index, handle_start, handle_move, handle_explain, handle_end],
         )
 }
@@ -0,0 +1,380 @@
+// A small neural network evaluation: a multilayer perceptron in plain Rust, quick enough on a CPU to
+// run several times within a move's timeout. Only built with the `neural` feature. It judges the boards
+// one turn after each move the look-ahead finds safe enough, see src/strategy/evaluated.rs.
+//
+// The network sees the board from our head, as planes over a window of VIEW squares either way: what's
+// off the board, our body, other snakes' bodies, the heads of snakes we'd lose a head-to-head to, the
+// heads of snakes we'd beat, food and hazards. Then the static evaluation's features, for what the
+// window can't show. One hidden layer of ReLUs feeds a logistic output, the chance of winning.
+//
+// It learns from positions labelled with how their games ended, from self-play and recorded games,
+// by stochastic gradient descent on the cross-entropy. `battlesnake-rusty-train` does both.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use log::{info, warn};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::arena::{self, ArenaConfig, Contestant};
+use crate::evaluation::{self, Evaluator, FEATURES};
+use crate::recorder::Record;
+use crate::session::GameSession;
+use crate::strategy::{Strategies, Strategy};
+use crate::texel;
+use crate::{Battlesnake, Board, GameState};
+
+// How far the network sees from our head
+pub const VIEW: i32 = 5;
+const SIDE: usize = (2 * VIEW + 1) as usize;
+pub const PLANES: usize = 7;
+pub const INPUTS: usize = PLANES * SIDE * SIDE + FEATURES;
+
+// Where the server looks for a trained network, unless `NEURAL_NETWORK` names another file
+pub const DEFAULT_NETWORK_PATH: &str = "network.json";
+
+const OFF_BOARD: usize = 0;
+const OUR_BODY: usize = 1;
+const THEIR_BODIES: usize = 2;
+const THREATS: usize = 3;
+const PREY: usize = 4;
+const FOOD: usize = 5;
+const HAZARDS: usize = 6;
+
+// What the network is given for `board`, seen by `you`
+pub fn inputs(board: &Board, you: &Battlesnake) -> Vec<f32> {
+    let mut inputs = vec![0.0; INPUTS];
+    let mut set = |plane: usize, x: i32, y: i32| {
+        let (dx, dy) = (x - you.head.x + VIEW, y - you.head.y + VIEW);
+        if (0..SIDE as i32).contains(&dx) && (0..SIDE as i32).contains(&dy) {
+            inputs[(plane * SIDE + dy as usize) * SIDE + dx as usize] = 1.0;
+        }
+    };
+
+    for dy in -VIEW..=VIEW {
+        for dx in -VIEW..=VIEW {
+            let (x, y) = (you.head.x + dx, you.head.y + dy);
+            if x < 0 || x >= board.width || y < 0 || y >= board.height {
+                set(OFF_BOARD, x, y);
+            }
+        }
+    }
+    for segment in you.body.iter().skip(1) {
+        set(OUR_BODY, segment.x, segment.y);
+    }
+    for snake in board.snakes.iter().filter(|s| s.id != you.id) {
+        for segment in &snake.body {
+            set(THEIR_BODIES, segment.x, segment.y);
+        }
+        let plane = if snake.length >= you.length { THREATS } else { PREY };
+        set(plane, snake.head.x, snake.head.y);
+    }
+    for food in &board.food {
+        set(FOOD, food.x, food.y);
+    }
+    for hazard in &board.hazards {
+        set(HAZARDS, hazard.x, hazard.y);
+    }
+
+    let features = evaluation::features(board, you);
+    for (input, feature) in inputs[PLANES * SIDE * SIDE..].iter_mut().zip(features) {
+        *input = feature as f32;
+    }
+    inputs
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Network {
+    pub hidden: usize,
+    // From each input to every hidden unit, input by input
+    weights: Vec<f32>,
+    biases: Vec<f32>,
+    output_weights: Vec<f32>,
+    output_bias: f32,
+}
+
+// A position ready for the network, and how its game ended
+#[derive(Debug, Clone)]
+pub struct Example {
+    pub inputs: Vec<f32>,
+    pub result: f64,
+}
+
+impl Network {
+    // An untrained network with `hidden` hidden units
+    pub fn new(hidden: usize, seed: u64) -> Network {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut uniform = |count: usize, fan_in: usize| -> Vec<f32> {
+            let range = (6.0 / fan_in as f32).sqrt();
+            (0..count).map(|_| rng.random_range(-range..range)).collect()
+        };
+        Network {
+            hidden,
+            weights: uniform(INPUTS * hidden, INPUTS),
+            biases: vec![0.0; hidden],
+            output_weights: uniform(hidden, hidden),
+            output_bias: 0.0,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Network, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let network: Network = serde_json::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if network.weights.len() != INPUTS * network.hidden
+            || network.biases.len() != network.hidden
+            || network.output_weights.len() != network.hidden
+        {
+            return Err(format!("{}: not a network for {} inputs", path.display(), INPUTS));
+        }
+        Ok(network)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let contents = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // The network `NEURAL_NETWORK` names, or the one in the working directory. None without one.
+    pub fn from_env() -> Option<Network> {
+        let path = env::var("NEURAL_NETWORK").unwrap_or_else(|_| DEFAULT_NETWORK_PATH.to_string());
+        if !Path::new(&path).exists() {
+            return None;
+        }
+        match Network::load(&path) {
+            Ok(network) => {
+                info!("Loaded a neural network with {} hidden units from {}", network.hidden, path);
+                Some(network)
+            }
+            Err(e) => {
+                warn!("Ignoring unreadable neural network {}", e);
+                None
+            }
+        }
+    }
+
+    // The hidden layer's activations and the output before the logistic function.
+    // Inputs are mostly zero, so only the others are looked at.
+    fn forward(&self, inputs: &[f32], hidden: &mut [f32]) -> f32 {
+        hidden.copy_from_slice(&self.biases);
+        for (i, &input) in inputs.iter().enumerate() {
+            if input != 0.0 {
+                let weights = &self.weights[i * self.hidden..(i + 1) * self.hidden];
+                for (h, w) in hidden.iter_mut().zip(weights) {
+                    *h += input * w;
+                }
+            }
+        }
+        let mut output = self.output_bias;
+        for (h, w) in hidden.iter_mut().zip(&self.output_weights) {
+            *h = h.max(0.0);
+            output += *h * w;
+        }
+        output
+    }
+
+    // Chance of winning, for inputs from `inputs`
+    pub fn predict(&self, inputs: &[f32]) -> f64 {
+        let mut hidden = vec![0.0; self.hidden];
+        evaluation::logistic(self.forward(inputs, &mut hidden) as f64)
+    }
+
+    // One pass over `examples` in a random order, a step after each. The mean cross-entropy before each step.
+    pub fn train_epoch(&mut self, examples: &[Example], learning_rate: f32, rng: &mut impl Rng) -> f64 {
+        let mut order: Vec<usize> = (0..examples.len()).collect();
+        order.shuffle(rng);
+        let mut hidden = vec![0.0; self.hidden];
+        let mut hidden_gradient = vec![0.0; self.hidden];
+        let mut loss = 0.0;
+
+        for &index in &order {
+            let example = &examples[index];
+            let chance = evaluation::logistic(self.forward(&example.inputs, &mut hidden) as f64);
+            let clamped = chance.clamp(1e-7, 1.0 - 1e-7);
+            loss -= example.result * clamped.ln() + (1.0 - example.result) * (1.0 - clamped).ln();
+
+            // The cross-entropy's slope at the output, before the logistic function, is just the miss
+            let miss = (chance - example.result) as f32;
+            for ((gradient, h), w) in hidden_gradient.iter_mut().zip(&hidden).zip(&self.output_weights) {
+                *gradient = if *h > 0.0 { miss * w } else { 0.0 };
+            }
+            for (w, h) in self.output_weights.iter_mut().zip(&hidden) {
+                *w -= learning_rate * miss * h;
+            }
+            self.output_bias -= learning_rate * miss;
+            for (b, gradient) in self.biases.iter_mut().zip(&hidden_gradient) {
+                *b -= learning_rate * gradient;
+            }
+            for (i, &input) in example.inputs.iter().enumerate() {
+                if input != 0.0 {
+                    let weights = &mut self.weights[i * self.hidden..(i + 1) * self.hidden];
+                    for (w, gradient) in weights.iter_mut().zip(&hidden_gradient) {
+                        *w -= learning_rate * gradient * input;
+                    }
+                }
+            }
+        }
+
+        loss / examples.len().max(1) as f64
+    }
+
+    // The mean squared difference between the chance of winning and the result, as `texel::error` has it
+    pub fn error(&self, examples: &[Example]) -> f64 {
+        if examples.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = examples
+            .iter()
+            .map(|example| (self.predict(&example.inputs) - example.result).powi(2))
+            .sum();
+        total / examples.len() as f64
+    }
+}
+
+impl Evaluator for Network {
+    fn evaluate(&self, board: &Board, you: &Battlesnake) -> f64 {
+        self.predict(&inputs(board, you))
+    }
+}
+
+// A position as it's exported for training, and how its game ended for `state.you`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrainingSample {
+    pub state: GameState,
+    pub result: f64,
+}
+
+impl TrainingSample {
+    pub fn example(&self) -> Example {
+        Example {
+            inputs: inputs(&self.state.board, &self.state.you),
+            result: self.result,
+        }
+    }
+}
+
+// Every position of a recorded game that went as far as /end
+pub fn from_recording(records: &[Record]) -> Vec<TrainingSample> {
+    texel::labelled(records)
+        .into_iter()
+        .map(|(state, result)| TrainingSample {
+            state: state.clone(),
+            result,
+        })
+        .collect()
+}
+
+// A strategy that remembers every board it's asked to move on
+struct Watched<'a> {
+    strategy: &'a dyn Strategy,
+    seen: Mutex<Vec<GameState>>,
+}
+
+impl Strategy for Watched<'_> {
+    fn info(&self) -> Value {
+        self.strategy.info()
+    }
+
+    fn start(&self, state: &GameState, session: &mut GameSession) {
+        self.strategy.start(state, session);
+    }
+
+    fn get_move(&self, state: &GameState, session: &mut GameSession) -> Value {
+        self.seen.lock().unwrap().push(state.clone());
+        self.strategy.get_move(state, session)
+    }
+
+    fn end(&self, state: &GameState, session: &mut GameSession) {
+        self.strategy.end(state, session);
+    }
+}
+
+// Every position of `games` arena games between the strategies `names`, `threads` games at a time.
+// Game seeds count up from the arena's.
+pub fn from_self_play(
+    config: &ArenaConfig,
+    strategies: &Strategies,
+    names: &[String],
+    games: u32,
+    threads: usize,
+) -> Result<Vec<TrainingSample>, String> {
+    if names.len() < 2 {
+        return Err("self-play needs at least two snakes".to_string());
+    }
+    for name in names {
+        if strategies.get(name).is_none() {
+            return Err(format!("unknown strategy: {}", name));
+        }
+    }
+
+    let samples = Mutex::new(Vec::new());
+    let error = Mutex::new(None);
+    let next_game = AtomicU32::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| {
+                loop {
+                    let game = next_game.fetch_add(1, Ordering::Relaxed);
+                    if game >= games || error.lock().unwrap().is_some() {
+                        break;
+                    }
+
+                    let watched: Vec<Watched> = names
+                        .iter()
+                        .map(|name| Watched {
+                            strategy: strategies.get(name).unwrap(),
+                            seen: Mutex::new(Vec::new()),
+                        })
+                        .collect();
+                    let contestants: Vec<Contestant> = names
+                        .iter()
+                        .zip(&watched)
+                        .enumerate()
+                        .map(|(i, (name, strategy))| Contestant {
+                            name: format!("{}-{}", name, i + 1),
+                            strategy,
+                        })
+                        .collect();
+                    let arena = ArenaConfig {
+                        seed: config.seed.wrapping_add(game as u64),
+                        ..config.clone()
+                    };
+
+                    let outcome = match arena::play_game(&arena, &contestants) {
+                        Ok(outcome) => outcome,
+                        Err(e) => {
+                            *error.lock().unwrap() = Some(e);
+                            break;
+                        }
+                    };
+                    let mut samples = samples.lock().unwrap();
+                    for strategy in watched {
+                        for state in strategy.seen.into_inner().unwrap() {
+                            let result = match &outcome.winner {
+                                Some(winner) if *winner == state.you.id => 1.0,
+                                Some(_) => 0.0,
+                                None => 0.5,
+                            };
+                            samples.push(TrainingSample { state, result });
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    if let Some(e) = error.into_inner().unwrap() {
+        return Err(e);
+    }
+    Ok(samples.into_inner().unwrap())
+}
@@ -0,0 +1,469 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+use crate::simulation::simulate_move;
+use crate::{Battlesnake, Board, Coord, Game, Move};
+
+// How much of our prediction for a snake near the timeout goes to it repeating its last move
+const NEAR_TIMEOUT_REPEAT_PROBABILITY: f64 = 0.5;
+
+// How often a snake picked a move with a given trait when it had the choice.
+// A trait only counts as "available" when some, but not all, of the snake's plausible moves had it,
+// otherwise the snake didn't really get to express a preference.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Tendency {
+    chosen: u32,
+    available: u32,
+}
+
+impl Tendency {
+    fn record(&mut self, chose_it: bool) {
+        self.available += 1;
+        if chose_it {
+            self.chosen += 1;
+        }
+    }
+
+    fn merge(&mut self, other: &Tendency) {
+        self.chosen += other.chosen;
+        self.available += other.available;
+    }
+
+    // Laplace-smoothed, so an opponent we haven't seen yet sits at an even 0.5
+    pub fn probability(&self) -> f64 {
+        (self.chosen as f64 + 1.0) / (self.available as f64 + 2.0)
+    }
+}
+
+// Reported response times, from the `latency` field the engine echoes back for every snake
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Latency {
+    total_ms: u64,
+    samples: u32,
+}
+
+impl Latency {
+    fn record(&mut self, latency: &str) {
+        if let Ok(ms) = latency.parse::<u64>() {
+            self.total_ms += ms;
+            self.samples += 1;
+        }
+    }
+
+    fn merge(&mut self, other: &Latency) {
+        self.total_ms += other.total_ms;
+        self.samples += other.samples;
+    }
+
+    pub fn mean_ms(&self) -> Option<f64> {
+        if self.samples == 0 {
+            None
+        } else {
+            Some(self.total_ms as f64 / self.samples as f64)
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct OpponentProfile {
+    pub food_greed: Tendency,
+    pub wall_hugging: Tendency,
+    pub head_to_head: Tendency,
+    pub tail_chasing: Tendency,
+    #[serde(default)]
+    pub latency: Latency,
+}
+
+impl OpponentProfile {
+    pub fn merge(&mut self, other: &OpponentProfile) {
+        self.food_greed.merge(&other.food_greed);
+        self.wall_hugging.merge(&other.wall_hugging);
+        self.head_to_head.merge(&other.head_to_head);
+        self.tail_chasing.merge(&other.tail_chasing);
+        self.latency.merge(&other.latency);
+    }
+}
+
+// When an opponent counts as close to timing out. Part of the profile's weights, in `[default.latency]`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LatencyThresholds {
+    // How many of an opponent's most recent latencies we look at for its trend
+    pub window: usize,
+    // A snake projected to answer slower than this fraction of the timeout is flagged as near it
+    pub near_timeout_fraction: f64,
+}
+
+impl Default for LatencyThresholds {
+    fn default() -> LatencyThresholds {
+        LatencyThresholds {
+            window: 5,
+            near_timeout_fraction: 0.8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Responsiveness {
+    Responsive,
+    NearTimeout,
+    TimedOut,
+}
+
+// An opponent's latency over the last few turns of this game.
+// The engine reports a snake that didn't answer in time with the full timeout as its latency.
+#[derive(Debug, Default, Clone)]
+struct LatencyTrend {
+    recent: VecDeque<u32>,
+}
+
+impl LatencyTrend {
+    fn record(&mut self, latency: &str, window: usize) {
+        if let Ok(ms) = latency.parse::<u32>() {
+            self.recent.push_back(ms);
+            while self.recent.len() > window.max(1) {
+                self.recent.pop_front();
+            }
+        }
+    }
+
+    fn responsiveness(&self, timeout: u32, thresholds: &LatencyThresholds) -> Responsiveness {
+        let (Some(&first), Some(&last)) = (self.recent.front(), self.recent.back()) else {
+            return Responsiveness::Responsive;
+        };
+
+        if timeout > 0 && last >= timeout {
+            return Responsiveness::TimedOut;
+        }
+
+        // Project one turn ahead using the average change across the window
+        let slope = if self.recent.len() > 1 {
+            (last as f64 - first as f64) / (self.recent.len() - 1) as f64
+        } else {
+            0.0
+        };
+        if timeout > 0 && last as f64 + slope >= timeout as f64 * thresholds.near_timeout_fraction {
+            Responsiveness::NearTimeout
+        } else {
+            Responsiveness::Responsive
+        }
+    }
+}
+
+// The traits a single candidate move would express
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MoveTraits {
+    pub food: bool,
+    pub wall: bool,
+    pub head_to_head: bool,
+    pub tail: bool,
+}
+
+// Everything the model knows about one snake's next move, looked up once so a search can ask
+// about the same snake over and over without going back to the profiles
+#[derive(Debug, Clone, Copy)]
+pub struct Predictor {
+    food_greed: f64,
+    wall_hugging: f64,
+    head_to_head: f64,
+    tail_chasing: f64,
+    repeat: Move,
+    // How much of the prediction goes to repeating the last move, none for a responsive snake
+    repeat_probability: Option<f64>,
+}
+
+impl Predictor {
+    // Probability distribution over a snake's plausible moves, given the traits of each.
+    // A snake that timed out repeats its last move, so it's certain to go there (or die trying),
+    // and a snake near the timeout gets part of its prediction shifted onto that repeat.
+    pub fn probabilities(&self, candidates: &[(Move, MoveTraits)]) -> Vec<(Move, f64)> {
+        let mut probabilities = [0.0; 4];
+        self.weigh(candidates, &mut probabilities);
+
+        let distribution = candidates.iter().zip(probabilities).map(|((move_dir, _), p)| (*move_dir, p));
+        if self.repeat_probability.is_none() {
+            distribution.collect()
+        } else {
+            distribution.filter(|(_, p)| *p > 0.0).collect()
+        }
+    }
+
+    // The probability of each of up to four candidates, in their order, without allocating.
+    // Moves a snake repeating into something won't survive come out as zero.
+    pub fn weigh(&self, candidates: &[(Move, MoveTraits)], probabilities: &mut [f64; 4]) {
+        self.behaviour_probabilities(candidates, probabilities);
+        let Some(repeat_probability) = self.repeat_probability else {
+            return;
+        };
+
+        // Repeating runs it into something, so only the chance it answers in time keeps it alive
+        let repeats = candidates.iter().any(|(move_dir, _)| *move_dir == self.repeat);
+        for ((move_dir, _), p) in candidates.iter().zip(probabilities.iter_mut()) {
+            let repeated = if repeats && *move_dir == self.repeat { repeat_probability } else { 0.0 };
+            *p = *p * (1.0 - repeat_probability) + repeated;
+        }
+    }
+
+    // Distribution from observed tendencies alone.
+    // Each trait a move has multiplies its weight by the tendency, each one it lacks by the complement.
+    fn behaviour_probabilities(&self, candidates: &[(Move, MoveTraits)], probabilities: &mut [f64; 4]) {
+        let weigh = |probability: f64, has: bool| if has { probability } else { 1.0 - probability };
+        for ((_, traits), weight) in candidates.iter().zip(probabilities.iter_mut()) {
+            *weight = weigh(self.food_greed, traits.food)
+                * weigh(self.wall_hugging, traits.wall)
+                * weigh(self.head_to_head, traits.head_to_head)
+                * weigh(self.tail_chasing, traits.tail);
+        }
+
+        let weights = &mut probabilities[..candidates.len()];
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            let uniform = 1.0 / weights.len() as f64;
+            weights.iter_mut().for_each(|w| *w = uniform);
+            return;
+        }
+        weights.iter_mut().for_each(|w| *w /= total);
+    }
+}
+
+// Profiles are keyed by snake id. What we learned in earlier games is kept apart as a prior,
+// so only this game's observations get written back to the profile store.
+#[derive(Debug, Default, Clone)]
+pub struct OpponentModel {
+    priors: HashMap<String, OpponentProfile>,
+    profiles: HashMap<String, OpponentProfile>,
+    names: HashMap<String, String>,
+    latency: HashMap<String, LatencyTrend>,
+    last_moves: HashMap<String, Move>,
+    timeout: u32,
+    thresholds: LatencyThresholds,
+    previous: Option<Board>,
+}
+
+fn distance(a: &Coord, b: &Coord) -> i32 {
+    (a.x - b.x).abs() + (a.y - b.y).abs()
+}
+
+// Moves a snake could make without running off the board or into a body segment.
+// Every tail is assumed to move out of the way, the same as the look-ahead assumes.
+pub fn plausible_moves(snake: &Battlesnake, board: &Board) -> Vec<Move> {
+    let mut moves = Vec::new();
+
+    for move_dir in Move::ALL {
+        let new_head = simulate_move(&snake.head, &move_dir);
+
+        if new_head.x < 0 || new_head.x >= board.width || new_head.y < 0 || new_head.y >= board.height {
+            continue;
+        }
+
+        let collision = board.snakes.iter().any(|other_snake| {
+            let tail = other_snake.body.len().saturating_sub(1);
+            other_snake
+                .body
+                .iter()
+                .enumerate()
+                .any(|(i, body_part)| !(i == tail && other_snake.body.len() > 1) && *body_part == new_head)
+        });
+
+        if !collision {
+            moves.push(move_dir);
+        }
+    }
+
+    moves
+}
+
+fn move_traits(snake: &Battlesnake, board: &Board, move_dir: &Move) -> MoveTraits {
+    let new_head = simulate_move(&snake.head, move_dir);
+
+    let nearest_food = board.food.iter().map(|food| distance(&snake.head, food)).min();
+    let food = match nearest_food {
+        Some(current) => board.food.iter().any(|food| distance(&new_head, food) < current),
+        None => false,
+    };
+
+    let wall = new_head.x == 0 || new_head.y == 0 || new_head.x == board.width - 1 || new_head.y == board.height - 1;
+
+    let head_to_head = board
+        .snakes
+        .iter()
+        .filter(|other| other.id != snake.id)
+        .any(|other| distance(&other.head, &new_head) <= 1);
+
+    let tail = match snake.body.last() {
+        Some(tail) if snake.body.len() > 2 => distance(&new_head, tail) < distance(&snake.head, tail),
+        _ => false,
+    };
+
+    MoveTraits { food, wall, head_to_head, tail }
+}
+
+// Work out which way a snake went between two turns from its head positions
+pub fn inferred_move(from: &Coord, to: &Coord) -> Option<Move> {
+    Move::ALL
+        .into_iter()
+        .find(|move_dir| simulate_move(from, move_dir) == *to)
+}
+
+impl OpponentModel {
+    // Start a snake off with what we already know about it from earlier games
+    pub fn warm(&mut self, snake: &Battlesnake, prior: OpponentProfile) {
+        self.names.insert(snake.id.clone(), snake.name.clone());
+        self.priors.insert(snake.id.clone(), prior);
+    }
+
+    // This game's observations for every opponent we've seen, as (id, name, profile)
+    pub fn observed(&self) -> impl Iterator<Item = (&str, &str, &OpponentProfile)> {
+        self.profiles.iter().filter_map(|(id, profile)| {
+            self.names
+                .get(id)
+                .map(|name| (id.as_str(), name.as_str(), profile))
+        })
+    }
+
+    // Prior and observations combined
+    fn profile(&self, snake_id: &str) -> OpponentProfile {
+        let mut profile = self.priors.get(snake_id).cloned().unwrap_or_default();
+        if let Some(observed) = self.profiles.get(snake_id) {
+            profile.merge(observed);
+        }
+        profile
+    }
+
+    // Judge opponents' latencies by `thresholds` from now on
+    pub fn set_latency_thresholds(&mut self, thresholds: LatencyThresholds) {
+        self.thresholds = thresholds;
+    }
+
+    // Whether a snake is keeping up with the game's timeout, going by its latency this game
+    pub fn responsiveness(&self, snake_id: &str) -> Responsiveness {
+        self.latency
+            .get(snake_id)
+            .map_or(Responsiveness::Responsive, |trend| trend.responsiveness(self.timeout, &self.thresholds))
+    }
+
+    // Compare this turn's board against the last one we saw and update every opponent's tendencies
+    pub fn observe(&mut self, game: &Game, board: &Board, you: &Battlesnake) {
+        self.timeout = game.timeout;
+
+        for snake in &board.snakes {
+            if snake.id == you.id {
+                continue;
+            }
+
+            self.names.insert(snake.id.clone(), snake.name.clone());
+            self.profiles.entry(snake.id.clone()).or_default().latency.record(&snake.latency);
+
+            let before = self.responsiveness(&snake.id);
+            self.latency.entry(snake.id.clone()).or_default().record(&snake.latency, self.thresholds.window);
+            let after = self.responsiveness(&snake.id);
+            if after != before {
+                info!("{} latency {}ms: {:?}", snake.name, snake.latency, after);
+            }
+        }
+
+        if let Some(previous) = self.previous.take() {
+            for snake in &board.snakes {
+                if snake.id == you.id {
+                    continue;
+                }
+
+                let Some(before) = previous.snakes.iter().find(|s| s.id == snake.id) else {
+                    continue;
+                };
+                let Some(chosen) = inferred_move(&before.head, &snake.head) else {
+                    continue;
+                };
+                self.last_moves.insert(snake.id.clone(), chosen);
+
+                let candidates = plausible_moves(before, &previous);
+                if !candidates.contains(&chosen) {
+                    continue;
+                }
+
+                let traits: Vec<(Move, MoveTraits)> = candidates
+                    .iter()
+                    .map(|move_dir| (*move_dir, move_traits(before, &previous, move_dir)))
+                    .collect();
+                let chosen_traits = move_traits(before, &previous, &chosen);
+
+                let profile = self.profiles.entry(snake.id.clone()).or_default();
+                let record = |tendency: &mut Tendency, has: fn(&MoveTraits) -> bool| {
+                    let with = traits.iter().filter(|(_, t)| has(t)).count();
+                    if with > 0 && with < traits.len() {
+                        tendency.record(has(&chosen_traits));
+                    }
+                };
+
+                record(&mut profile.food_greed, |t| t.food);
+                record(&mut profile.wall_hugging, |t| t.wall);
+                record(&mut profile.head_to_head, |t| t.head_to_head);
+                record(&mut profile.tail_chasing, |t| t.tail);
+            }
+        }
+
+        self.previous = Some(board.clone());
+    }
+
+    // What the model predicts for a snake, prior and observations combined
+    pub fn predictor(&self, snake_id: &str) -> Predictor {
+        let profile = self.profile(snake_id);
+        let repeat_probability = match self.responsiveness(snake_id) {
+            Responsiveness::TimedOut => Some(1.0),
+            Responsiveness::NearTimeout => Some(NEAR_TIMEOUT_REPEAT_PROBABILITY),
+            Responsiveness::Responsive => None,
+        };
+
+        Predictor {
+            food_greed: profile.food_greed.probability(),
+            wall_hugging: profile.wall_hugging.probability(),
+            head_to_head: profile.head_to_head.probability(),
+            tail_chasing: profile.tail_chasing.probability(),
+            // The engine plays "up" for a snake that never answered at all
+            repeat: self.last_moves.get(snake_id).copied().unwrap_or(Move::Up),
+            repeat_probability,
+        }
+    }
+
+    // Probability distribution over a snake's plausible next moves
+    pub fn move_probabilities(&self, snake: &Battlesnake, board: &Board) -> Vec<(Move, f64)> {
+        let candidates: Vec<(Move, MoveTraits)> = plausible_moves(snake, board)
+            .into_iter()
+            .map(|move_dir| (move_dir, move_traits(snake, board, &move_dir)))
+            .collect();
+        self.predictor(&snake.id).probabilities(&candidates)
+    }
+
+    // The single most likely reply, ties going to the first candidate in Up, Down, Left, Right order
+    pub fn most_likely_move(&self, snake: &Battlesnake, board: &Board) -> Option<Move> {
+        let mut best: Option<(Move, f64)> = None;
+        for (move_dir, probability) in self.move_probabilities(snake, board) {
+            if best.is_none_or(|(_, p)| probability > p) {
+                best = Some((move_dir, probability));
+            }
+        }
+        best.map(|(move_dir, _)| move_dir)
+    }
+
+    // Probability that at least one snake at least as long as us moves its head onto `pos` next turn
+    pub fn head_to_head_risk(&self, pos: &Coord, board: &Board, you: &Battlesnake) -> f64 {
+        let mut survival = 1.0;
+
+        for snake in &board.snakes {
+            if snake.id == you.id || snake.length < you.length || distance(&snake.head, pos) != 1 {
+                continue;
+            }
+
+            let probability: f64 = self
+                .move_probabilities(snake, board)
+                .iter()
+                .filter(|(move_dir, _)| simulate_move(&snake.head, move_dir) == *pos)
+                .map(|(_, p)| p)
+                .sum();
+            survival *= 1.0 - probability;
+        }
+
+        1.0 - survival
+    }
+}
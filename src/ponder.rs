@@ -0,0 +1,92 @@
+// Pondering: searching on while the other snakes make their moves.
+//
+// Once we've answered a /move, the likeliest ways opponents could reply to our move each give a board
+// we could be asked about next. The look-ahead runs on each of them in the background, likeliest first,
+// and what it finds goes into the game's transposition table. If one of them is the next board, the
+// next search finds its work already done. The next request for the game stops pondering first.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use log::debug;
+
+use crate::bitboard::{Bits, Search, WideBits};
+use crate::logic::LOOK_AHEAD_DEPTH;
+use crate::session::GameSession;
+use crate::transposition::TranspositionTable;
+use crate::{GameState, Move};
+
+// How many of the opponents' likeliest replies to search ahead on
+pub const PONDER_REPLIES: usize = 8;
+
+// A game's pondering, running until it's done or stopped
+#[derive(Clone)]
+pub struct Pondering {
+    stop: Arc<AtomicBool>,
+    thread: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl Pondering {
+    // Stops pondering, and waits until it has
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.wait();
+    }
+
+    // Waits for pondering to run its course
+    pub fn wait(&self) {
+        if let Some(thread) = self.thread.lock().unwrap().take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl fmt::Debug for Pondering {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Pondering").field("stopped", &self.stop.load(Ordering::Relaxed)).finish()
+    }
+}
+
+// Starts pondering on the boards that could follow our `move_dir` on `state`, at the depth the game is
+// searching at and with the head-to-head `risk_limit` it searches with, so what it finds is what the
+// next search would. None when the board is one only the search on `Board` copes with.
+pub fn start(state: &GameState, move_dir: Move, session: &GameSession, risk_limit: f64) -> Option<Pondering> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let depth = session.depth.unwrap_or(LOOK_AHEAD_DEPTH);
+    let table = session.transpositions.clone();
+    let (board, you) = (&state.board, &state.you);
+
+    let thread = if board.width.max(0) * board.height.max(0) <= 128 {
+        let search = Search::<u128>::new(board, you, &session.opponents, risk_limit)?.with_stop(stop.clone());
+        thread::spawn(move || ponder(search, move_dir, depth, &table))
+    } else {
+        let search = Search::<WideBits>::new(board, you, &session.opponents, risk_limit)?.with_stop(stop.clone());
+        thread::spawn(move || ponder(search, move_dir, depth, &table))
+    };
+
+    Some(Pondering {
+        stop,
+        thread: Arc::new(Mutex::new(Some(thread))),
+    })
+}
+
+fn ponder<B: Bits>(search: Search<B>, move_dir: Move, depth: i32, table: &TranspositionTable) {
+    let predictions = search.predict(&search.root);
+    let replies = search.likely_replies(&search.root, &predictions, PONDER_REPLIES);
+
+    for (i, (chance, reply)) in replies.iter().enumerate() {
+        if search.stopped() {
+            return;
+        }
+        let next = search.advance(&search.root, reply, move_dir);
+        if next.us().is_none() {
+            continue;
+        }
+        for next_move in Move::ALL {
+            search.safety_from(&next, next_move, depth, table);
+        }
+        debug!("Pondered reply {} of {} ({:.0}% likely)", i + 1, replies.len(), chance * 100.0);
+    }
+}
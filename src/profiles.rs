@@ -0,0 +1,131 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::opponent::{OpponentModel, OpponentProfile};
+use crate::{Battlesnake, Board};
+
+// Everything we know about an opponent, keyed by its name since ids change every game
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct StoredProfile {
+    pub behaviour: OpponentProfile,
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+// Opponent profiles persisted to a JSON file between games
+#[derive(Debug)]
+pub struct ProfileStore {
+    profiles: Mutex<HashMap<String, StoredProfile>>,
+    // Saving happens on its own thread, so /end never waits on the disk
+    saves: Option<Sender<HashMap<String, StoredProfile>>>,
+    saver: Option<JoinHandle<()>>,
+}
+
+impl ProfileStore {
+    // Load the store from disk, starting empty if the file is missing or unreadable
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let profiles = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable opponent profiles in {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        info!("Loaded {} opponent profiles from {}", profiles.len(), path.display());
+        let (saves, pending) = mpsc::channel::<HashMap<String, StoredProfile>>();
+        let saver = thread::spawn(move || {
+            while let Ok(mut profiles) = pending.recv() {
+                // Games that ended while we were writing only need their latest snapshot saved
+                if let Some(latest) = pending.try_iter().last() {
+                    profiles = latest;
+                }
+                if let Err(e) = save(&path, &profiles) {
+                    warn!("Failed to save opponent profiles to {}: {}", path.display(), e);
+                }
+            }
+        });
+
+        ProfileStore {
+            profiles: Mutex::new(profiles),
+            saves: Some(saves),
+            saver: Some(saver),
+        }
+    }
+
+    // Seed the opponent model with the stored behaviour of every opponent we recognise
+    pub fn warm(&self, model: &mut OpponentModel, board: &Board, you: &Battlesnake) {
+        let profiles = self.profiles.lock().unwrap();
+        for snake in &board.snakes {
+            if snake.id == you.id {
+                continue;
+            }
+            if let Some(stored) = profiles.get(&snake.name) {
+                info!(
+                    "Recognised {}: {} games, {}W/{}L/{}D, typical latency {}",
+                    snake.name,
+                    stored.games,
+                    stored.wins,
+                    stored.losses,
+                    stored.draws,
+                    stored
+                        .behaviour
+                        .latency
+                        .mean_ms()
+                        .map_or("unknown".to_string(), |ms| format!("{:.0}ms", ms)),
+                );
+                model.warm(snake, stored.behaviour.clone());
+            }
+        }
+    }
+
+    // Fold a finished game into the store and queue it to be written back to disk.
+    // Against each opponent it's a win if only we survived, a loss if only they did, and a draw otherwise.
+    pub fn record_game(&self, model: &OpponentModel, board: &Board, you: &Battlesnake) {
+        let alive = |id: &str| board.snakes.iter().any(|snake| snake.id == id);
+        let we_survived = alive(&you.id);
+
+        let mut profiles = self.profiles.lock().unwrap();
+        for (id, name, observed) in model.observed() {
+            let stored = profiles.entry(name.to_string()).or_default();
+            stored.behaviour.merge(observed);
+            stored.games += 1;
+            match (we_survived, alive(id)) {
+                (true, false) => stored.wins += 1,
+                (false, true) => stored.losses += 1,
+                _ => stored.draws += 1,
+            }
+        }
+
+        if let Some(saves) = &self.saves {
+            let _ = saves.send(profiles.clone());
+        }
+    }
+}
+
+// Finish any queued save before going away
+impl Drop for ProfileStore {
+    fn drop(&mut self) {
+        self.saves.take();
+        if let Some(saver) = self.saver.take() {
+            let _ = saver.join();
+        }
+    }
+}
+
+// Write to a temporary file first so a crash can't leave a half-written store behind
+fn save(path: &Path, profiles: &HashMap<String, StoredProfile>) -> std::io::Result<()> {
+    let contents = serde_json::to_string_pretty(profiles)?;
+    let temporary = path.with_extension("json.tmp");
+    fs::write(&temporary, contents)?;
+    fs::rename(&temporary, path)
+}
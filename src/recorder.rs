@@ -0,0 +1,162 @@
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::strategy::chosen_move;
+use crate::{GameState, Move};
+
+// Keeps every request of a game, and what we answered, in `<dir>/<game id>.jsonl`, so bad decisions
+// can be reproduced long after the logs are gone. One JSON record per line, in the order they arrived.
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Event {
+    Start,
+    Move,
+    End,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Record {
+    pub event: Event,
+    // The snake that answered, e.g. "rusty"
+    pub snake: String,
+    pub state: GameState,
+    // Only set for moves
+    #[serde(rename = "move")]
+    pub move_dir: Option<Move>,
+    pub shout: Option<String>,
+    pub scores: HashMap<Move, f64>,
+    pub response_ms: f64,
+}
+
+impl Record {
+    pub fn new(event: Event, snake: &str, state: &GameState) -> Record {
+        Record {
+            event,
+            snake: snake.to_string(),
+            state: state.clone(),
+            move_dir: None,
+            shout: None,
+            scores: HashMap::new(),
+            response_ms: 0.0,
+        }
+    }
+
+    // Fill in the move and shout from a /move response
+    pub fn with_response(mut self, response: &Value) -> Record {
+        self.move_dir = chosen_move(response);
+        self.shout = response.get("shout").and_then(Value::as_str).map(str::to_string);
+        self
+    }
+}
+
+// Disabled unless given a directory
+#[derive(Debug, Default)]
+pub struct Recorder {
+    // Writing happens on its own thread, so no request waits on the disk. Having the one thread write
+    // also keeps two of our snakes in the same game from writing to their shared file at once.
+    records: Option<Sender<Record>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+// Game ids come from the engine, so keep them from escaping the directory
+fn file_name(game_id: &str) -> String {
+    let safe: String = game_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}.jsonl", safe)
+}
+
+impl Recorder {
+    pub fn new(dir: Option<PathBuf>) -> Recorder {
+        let Some(dir) = dir else {
+            return Recorder::default();
+        };
+
+        let (records, pending) = mpsc::channel::<Record>();
+        let writer = thread::spawn(move || {
+            for record in pending {
+                let path = dir.join(file_name(&record.state.game.id));
+                if let Err(e) = append(&path, &record) {
+                    warn!("Couldn't record {:?} to {}: {}", record.event, path.display(), e);
+                }
+            }
+        });
+
+        Recorder {
+            records: Some(records),
+            writer: Some(writer),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.records.is_some()
+    }
+
+    // Queues `record` to be written. Failing to record never fails the request it's recording.
+    pub fn record(&self, record: Record) {
+        if let Some(records) = &self.records {
+            let _ = records.send(record);
+        }
+    }
+}
+
+// Finish writing everything queued before going away
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.records.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+fn append(path: &Path, record: &Record) -> Result<(), String> {
+    let mut line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    line.push('\n');
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path).map_err(|e| e.to_string())?;
+    file.write_all(line.as_bytes()).map_err(|e| e.to_string())
+}
+
+// Every record of a recorded game, in the order they were written
+pub fn load(path: &Path) -> Result<Vec<Record>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("{}:{}: {}", path.display(), i + 1, e)))
+        .collect()
+}
+
+// Recordings named directly, plus every .jsonl file in named directories
+pub fn recordings(paths: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut found = Vec::new();
+    for path in paths.iter().map(PathBuf::from) {
+        if !path.is_dir() {
+            found.push(path);
+            continue;
+        }
+
+        let mut files: Vec<PathBuf> = fs::read_dir(&path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|file| file.extension().is_some_and(|ext| ext == "jsonl"))
+            .collect();
+        files.sort();
+        found.extend(files);
+    }
+    Ok(found)
+}
@@ -0,0 +1,160 @@
+use log::warn;
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::GameState;
+use crate::session::GameSession;
+use crate::strategy::Strategy;
+
+// A snake served over HTTP, our own Rocket server or anyone else's, so the arena can referee it
+// exactly like one of our built-in strategies. Plain HTTP only, which is all a local snake needs.
+
+// The index isn't part of a game, so it gets its own, more forgiving, timeout
+const INDEX_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub struct RemoteSnake {
+    host: String,
+    port: u16,
+    // Everything before the endpoint, e.g. "/rusty" for http://localhost:8000/rusty
+    prefix: String,
+}
+
+impl RemoteSnake {
+    pub fn new(url: &str) -> Result<RemoteSnake, String> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("unsupported snake url (only http:// is): {}", url))?;
+        let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|e| format!("invalid port in {}: {}", url, e))?),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(format!("missing host in snake url: {}", url));
+        }
+
+        Ok(RemoteSnake {
+            host: host.to_string(),
+            port,
+            prefix: path.trim_end_matches('/').to_string(),
+        })
+    }
+
+    // The path for an endpoint, with the index being the snake's url itself
+    fn path(&self, endpoint: &str) -> String {
+        match format!("{}{}", self.prefix, endpoint) {
+            path if path.is_empty() => "/".to_string(),
+            path => path,
+        }
+    }
+
+    fn request(&self, method: &str, endpoint: &str, state: Option<&GameState>, timeout: Duration) -> Result<Value, String> {
+        let address = (self.host.as_str(), self.port)
+            .to_socket_addrs()
+            .map_err(|e| e.to_string())?
+            .next()
+            .ok_or_else(|| format!("{} doesn't resolve", self.host))?;
+        let mut stream = TcpStream::connect_timeout(&address, timeout).map_err(|e| e.to_string())?;
+        stream.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+        stream.set_write_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+
+        let body = match state {
+            Some(state) => serde_json::to_string(state).map_err(|e| e.to_string())?,
+            None => String::new(),
+        };
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: {}:{}\r\nUser-Agent: battlesnake-rusty-arena\r\nAccept: application/json\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            self.path(endpoint),
+            self.host,
+            self.port,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).map_err(|e| e.to_string())?;
+        parse_response(&response)
+    }
+
+    fn post(&self, endpoint: &str, state: &GameState) -> Value {
+        let timeout = Duration::from_millis(state.game.timeout as u64);
+        self.request("POST", endpoint, Some(state), timeout).unwrap_or_else(|e| {
+            warn!("POST {} to {}:{} failed: {}", self.path(endpoint), self.host, self.port, e);
+            Value::Null
+        })
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn parse_response(response: &[u8]) -> Result<Value, String> {
+    let split = find(response, b"\r\n\r\n").ok_or("malformed HTTP response")?;
+    let head = String::from_utf8_lossy(&response[..split]);
+    let mut body = response[split + 4..].to_vec();
+
+    let mut lines = head.lines();
+    let status = lines.next().and_then(|line| line.split_whitespace().nth(1)).ok_or("missing HTTP status")?;
+    if status != "200" {
+        return Err(format!("HTTP status {}", status));
+    }
+
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding") && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+    if chunked {
+        body = dechunk(&body)?;
+    }
+
+    // /start and /end don't have to answer with anything
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(Value::Null);
+    }
+    serde_json::from_slice(&body).map_err(|e| e.to_string())
+}
+
+fn dechunk(mut body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = find(body, b"\r\n").ok_or("malformed chunk")?;
+        let size = String::from_utf8_lossy(&body[..line_end]);
+        let size = usize::from_str_radix(size.split(';').next().unwrap_or("").trim(), 16).map_err(|e| e.to_string())?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Ok(decoded);
+        }
+
+        decoded.extend_from_slice(body.get(..size).ok_or("truncated chunk")?);
+        body = body.get(size + 2..).ok_or("truncated chunk")?;
+    }
+}
+
+impl Strategy for RemoteSnake {
+    fn info(&self) -> Value {
+        self.request("GET", "", None, INDEX_TIMEOUT).unwrap_or_else(|e| {
+            warn!("GET {} from {}:{} failed: {}", self.path(""), self.host, self.port, e);
+            Value::Null
+        })
+    }
+
+    fn start(&self, state: &GameState, _session: &mut GameSession) {
+        self.post("/start", state);
+    }
+
+    // A snake that fails to answer in time gets no move, which the arena turns into the default one
+    fn get_move(&self, state: &GameState, _session: &mut GameSession) -> Value {
+        self.post("/move", state)
+    }
+
+    fn end(&self, state: &GameState, _session: &mut GameSession) {
+        self.post("/end", state);
+    }
+}
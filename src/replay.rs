@@ -0,0 +1,190 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::collections::HashMap;
+
+use crate::opponent::inferred_move;
+use crate::recorder::{Event, Record};
+use crate::rules::{self, Settings};
+use crate::session::GameSession;
+use crate::strategy::{Strategies, Strategy, chosen_move};
+use crate::{Board, GameState, Move};
+
+// Re-runs recorded games through the current build, turn by turn, to find where it now decides
+// differently and whether that would have changed how the game went
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    // A different move, with no known difference in outcome
+    Changed,
+    // The recorded move died within the horizon, the new one survives it in simulation
+    AvoidsDeath,
+    // The recorded move survived the horizon, the new one dies in simulation
+    Regression,
+}
+
+#[derive(Debug, Clone)]
+pub struct Difference {
+    pub turn: i32,
+    pub recorded: Move,
+    pub replayed: Move,
+    pub verdict: Verdict,
+}
+
+// One of our snakes in a recorded game
+#[derive(Debug, Clone)]
+pub struct ReplayedGame {
+    pub game_id: String,
+    pub snake_name: String,
+    pub strategy: String,
+    pub moves: usize,
+    // The turn we were eliminated on, when the recording shows it
+    pub death_turn: Option<i32>,
+    pub differences: Vec<Difference>,
+}
+
+// Replay every snake of ours in a recorded game with `strategy`, or whichever strategy played it.
+// Deaths count against a move when they happen within `horizon` turns of it.
+pub fn replay(records: &[Record], strategies: &Strategies, strategy: Option<&str>, horizon: i32) -> Result<Vec<ReplayedGame>, String> {
+    let mut snakes: Vec<&str> = Vec::new();
+    for record in records {
+        if !snakes.contains(&record.state.you.id.as_str()) {
+            snakes.push(&record.state.you.id);
+        }
+    }
+
+    snakes
+        .into_iter()
+        .map(|snake_id| {
+            let records: Vec<&Record> = records.iter().filter(|r| r.state.you.id == snake_id).collect();
+            replay_snake(&records, strategies, strategy, horizon)
+        })
+        .collect()
+}
+
+fn replay_snake(records: &[&Record], strategies: &Strategies, strategy: Option<&str>, horizon: i32) -> Result<ReplayedGame, String> {
+    let first = records[0];
+    let name = strategy.unwrap_or(&first.snake);
+    let player = strategies.get(name).ok_or_else(|| format!("unknown strategy: {}", name))?;
+    // Games on rulesets we can't simulate still show where the choices differ
+    let settings = Settings::from_ruleset_json(&first.state.game.ruleset).ok();
+
+    let moves: Vec<&Record> = records.iter().copied().filter(|r| r.event == Event::Move).collect();
+    // The engine stops asking an eliminated snake for moves, but still tells it the game ended
+    let death_turn = records
+        .iter()
+        .find(|r| r.event == Event::End)
+        .filter(|end| !end.state.board.snakes.iter().any(|s| s.id == first.state.you.id))
+        .and(moves.last())
+        .map(|last| last.state.turn + 1);
+    let boards: HashMap<i32, &Board> = records.iter().map(|r| (r.state.turn, &r.state.board)).collect();
+
+    let mut session = GameSession::default();
+    session.opponents.set_latency_thresholds(player.weights(&first.state.game).latency);
+    let mut differences = Vec::new();
+    for record in records {
+        match record.event {
+            Event::Start => player.start(&record.state, &mut session),
+            Event::End => {}
+            Event::Move => {
+                let state = &record.state;
+                session.opponents.observe(&state.game, &state.board, &state.you);
+                let before = session.clone();
+                session.scores.clear();
+
+                let replayed = chosen_move(&player.get_move(state, &mut session));
+                let (Some(recorded), Some(replayed)) = (record.move_dir, replayed) else {
+                    continue;
+                };
+                if recorded == replayed {
+                    continue;
+                }
+
+                let recorded_died = death_turn.is_some_and(|death| death - state.turn <= horizon);
+                let verdict = match &settings {
+                    Some(settings) => {
+                        let survives = survives(state, replayed, &boards, before, player, settings, horizon);
+                        match (recorded_died, survives) {
+                            (true, true) => Verdict::AvoidsDeath,
+                            (false, false) => Verdict::Regression,
+                            _ => Verdict::Changed,
+                        }
+                    }
+                    None => Verdict::Changed,
+                };
+                differences.push(Difference {
+                    turn: state.turn,
+                    recorded,
+                    replayed,
+                    verdict,
+                });
+            }
+        }
+    }
+
+    Ok(ReplayedGame {
+        game_id: first.state.game.id.clone(),
+        snake_name: first.state.you.name.clone(),
+        strategy: name.to_string(),
+        moves: moves.len(),
+        death_turn,
+        differences,
+    })
+}
+
+// Play `first` and then whatever the strategy picks for up to `horizon` turns. Opponents repeat what
+// they really did where the recording shows it, and fall back to their most likely move after that.
+// Food that spawned later can't be known, so none spawns.
+fn survives(
+    from: &GameState,
+    first: Move,
+    recorded: &HashMap<i32, &Board>,
+    mut session: GameSession,
+    player: &dyn Strategy,
+    settings: &Settings,
+    horizon: i32,
+) -> bool {
+    let settings = Settings {
+        food_spawn_chance: 0,
+        minimum_food: 0,
+        ..settings.clone()
+    };
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut board = from.board.clone();
+    let mut our_move = first;
+
+    for step in 0..horizon {
+        let turn = from.turn + step;
+        let mut moves = HashMap::from([(from.you.id.clone(), our_move)]);
+        for snake in board.snakes.iter().filter(|s| s.id != from.you.id) {
+            let head_at = |turn: i32| {
+                recorded
+                    .get(&turn)
+                    .and_then(|board| board.snakes.iter().find(|s| s.id == snake.id))
+                    .map(|s| s.head.clone())
+            };
+            let actual = head_at(turn).zip(head_at(turn + 1)).and_then(|(now, next)| inferred_move(&now, &next));
+            if let Some(move_dir) = actual.or_else(|| session.opponents.most_likely_move(snake, &board)) {
+                moves.insert(snake.id.clone(), move_dir);
+            }
+        }
+
+        rules::step(&mut board, turn + 1, &moves, &settings, &mut rng);
+        let Some(you) = board.snakes.iter().find(|s| s.id == from.you.id) else {
+            return false;
+        };
+        if rules::is_game_over(&board, &settings) || step + 1 == horizon {
+            return true;
+        }
+
+        let state = GameState {
+            game: from.game.clone(),
+            turn: turn + 1,
+            board: board.clone(),
+            you: you.clone(),
+        };
+        session.opponents.observe(&state.game, &state.board, &state.you);
+        our_move = chosen_move(&player.get_move(&state, &mut session)).unwrap_or_else(|| rules::default_move(&state.you));
+    }
+
+    true
+}
@@ -0,0 +1,390 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::simulation::{coords_equal, simulate_move};
+use crate::{Battlesnake, Board, Coord, Move};
+
+// A port of the official Battlesnake rules, see https://github.com/BattlesnakeOfficial/rules
+// Only the rulesets our snakes play are covered.
+
+pub const SNAKE_MAX_HEALTH: i32 = 100;
+pub const SNAKE_START_SIZE: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ruleset {
+    Standard,
+    Solo,
+    Constrictor,
+}
+
+impl Ruleset {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ruleset::Standard => "standard",
+            Ruleset::Solo => "solo",
+            Ruleset::Constrictor => "constrictor",
+        }
+    }
+}
+
+impl FromStr for Ruleset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Ruleset::Standard),
+            "solo" => Ok(Ruleset::Solo),
+            "constrictor" => Ok(Ruleset::Constrictor),
+            _ => Err(format!("unsupported ruleset: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub ruleset: Ruleset,
+    pub food_spawn_chance: u32,
+    pub minimum_food: u32,
+    pub hazard_damage_per_turn: i32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            ruleset: Ruleset::Standard,
+            food_spawn_chance: 15,
+            minimum_food: 1,
+            hazard_damage_per_turn: 14,
+        }
+    }
+}
+
+impl Settings {
+    // The `ruleset` object the engine sends snakes in `game`
+    pub fn to_ruleset_json(&self) -> HashMap<String, Value> {
+        HashMap::from([
+            ("name".to_string(), json!(self.ruleset.as_str())),
+            ("version".to_string(), json!("battlesnake-rusty")),
+            (
+                "settings".to_string(),
+                json!({
+                    "foodSpawnChance": self.food_spawn_chance,
+                    "minimumFood": self.minimum_food,
+                    "hazardDamagePerTurn": self.hazard_damage_per_turn,
+                }),
+            ),
+        ])
+    }
+
+    // Read back the `ruleset` object from a game, falling back to the defaults for missing settings
+    pub fn from_ruleset_json(ruleset: &HashMap<String, Value>) -> Result<Settings, String> {
+        let defaults = Settings::default();
+        let name = ruleset.get("name").and_then(Value::as_str).unwrap_or(defaults.ruleset.as_str());
+        let setting = |key: &str| ruleset.get("settings").and_then(|settings| settings.get(key)).and_then(Value::as_i64);
+
+        Ok(Settings {
+            ruleset: name.parse()?,
+            food_spawn_chance: setting("foodSpawnChance").map_or(defaults.food_spawn_chance, |chance| chance as u32),
+            minimum_food: setting("minimumFood").map_or(defaults.minimum_food, |food| food as u32),
+            hazard_damage_per_turn: setting("hazardDamagePerTurn").map_or(defaults.hazard_damage_per_turn, |damage| damage as i32),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EliminationCause {
+    OutOfHealth,
+    WallCollision,
+    SelfCollision,
+    SnakeCollision,
+    HeadCollision,
+}
+
+impl EliminationCause {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EliminationCause::OutOfHealth => "out-of-health",
+            EliminationCause::WallCollision => "wall-collision",
+            EliminationCause::SelfCollision => "snake-self-collision",
+            EliminationCause::SnakeCollision => "snake-collision",
+            EliminationCause::HeadCollision => "head-collision",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Elimination {
+    pub snake_id: String,
+    pub cause: EliminationCause,
+    // The snake it ran into, for collisions with another snake
+    pub by: Option<String>,
+    pub turn: i32,
+}
+
+impl fmt::Display for Elimination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on turn {}", self.cause.as_str(), self.turn)?;
+        if let Some(by) = &self.by {
+            write!(f, " (by {})", by)?;
+        }
+        Ok(())
+    }
+}
+
+fn unoccupied_points(board: &Board) -> Vec<Coord> {
+    let mut points = Vec::new();
+    for x in 0..board.width {
+        for y in 0..board.height {
+            let point = Coord { x, y };
+            let occupied = board.food.contains(&point)
+                || board.snakes.iter().any(|snake| snake.body.contains(&point));
+            if !occupied {
+                points.push(point);
+            }
+        }
+    }
+    points
+}
+
+// Lay out a fresh board the way the engine does: snakes stacked on their start squares,
+// fixed start positions and food on the standard board sizes, random ones otherwise
+pub fn create_board(width: i32, height: i32, snakes: &[(String, String)], settings: &Settings, rng: &mut impl Rng) -> Result<Board, String> {
+    let mut board = Board {
+        height,
+        width,
+        food: Vec::new(),
+        snakes: Vec::new(),
+        hazards: Vec::new(),
+    };
+
+    let fixed = width == height && [7, 11, 19].contains(&width);
+    let starts = if fixed {
+        let (mn, md, mx) = (1, (width - 1) / 2, width - 2);
+        let mut corners = vec![
+            Coord { x: mn, y: mn },
+            Coord { x: mn, y: mx },
+            Coord { x: mx, y: mn },
+            Coord { x: mx, y: mx },
+        ];
+        let mut cardinals = vec![
+            Coord { x: mn, y: md },
+            Coord { x: md, y: mn },
+            Coord { x: md, y: mx },
+            Coord { x: mx, y: md },
+        ];
+        corners.shuffle(rng);
+        cardinals.shuffle(rng);
+        // Either group may be handed out first
+        if rng.random_bool(0.5) {
+            std::mem::swap(&mut corners, &mut cardinals);
+        }
+        corners.into_iter().chain(cardinals).collect::<Vec<_>>()
+    } else {
+        // Random starts stick to even squares, as the engine's do
+        let mut points: Vec<Coord> = unoccupied_points(&board)
+            .into_iter()
+            .filter(|point| (point.x + point.y) % 2 == 0)
+            .collect();
+        points.shuffle(rng);
+        points
+    };
+
+    if starts.len() < snakes.len() {
+        return Err(format!("a {}x{} board doesn't have room for {} snakes", width, height, snakes.len()));
+    }
+
+    for ((id, name), start) in snakes.iter().zip(starts) {
+        board.snakes.push(Battlesnake {
+            id: id.clone(),
+            name: name.clone(),
+            health: SNAKE_MAX_HEALTH,
+            body: vec![start.clone(); SNAKE_START_SIZE],
+            head: start,
+            length: SNAKE_START_SIZE as i32,
+            latency: String::new(),
+            shout: None,
+        });
+    }
+
+    if settings.ruleset == Ruleset::Constrictor {
+        return Ok(board);
+    }
+
+    if fixed {
+        // One food diagonally next to each snake, further from the centre than the snake on at least
+        // one axis and never in a corner, then one in the centre
+        let centre = Coord { x: (width - 1) / 2, y: (height - 1) / 2 };
+        for i in 0..board.snakes.len() {
+            let head = board.snakes[i].head.clone();
+            let mut options: Vec<Coord> = [(-1, -1), (-1, 1), (1, -1), (1, 1)]
+                .iter()
+                .map(|(dx, dy)| Coord { x: head.x + dx, y: head.y + dy })
+                .filter(|food| {
+                    let away = (food.x < head.x && head.x < centre.x)
+                        || (centre.x < head.x && head.x < food.x)
+                        || (food.y < head.y && head.y < centre.y)
+                        || (centre.y < head.y && head.y < food.y);
+                    let corner = (food.x == 0 || food.x == width - 1) && (food.y == 0 || food.y == height - 1);
+                    away && !corner && !coords_equal(food, &centre) && unoccupied_points(&board).contains(food)
+                })
+                .collect();
+            options.shuffle(rng);
+            if let Some(food) = options.pop() {
+                board.food.push(food);
+            }
+        }
+        if unoccupied_points(&board).contains(&centre) {
+            board.food.push(centre);
+        }
+    } else {
+        let count = board.snakes.len();
+        spawn_food(&mut board, count, rng);
+    }
+
+    Ok(board)
+}
+
+fn spawn_food(board: &mut Board, count: usize, rng: &mut impl Rng) {
+    let mut points = unoccupied_points(board);
+    points.shuffle(rng);
+    board.food.extend(points.into_iter().take(count));
+}
+
+// The snake's next move when it gave none: carry on in the direction it's facing, or up if it can't tell
+pub fn default_move(snake: &Battlesnake) -> Move {
+    if snake.body.len() < 2 {
+        return Move::Up;
+    }
+    Move::ALL
+        .into_iter()
+        .find(|move_dir| coords_equal(&simulate_move(&snake.body[1], move_dir), &snake.body[0]))
+        .unwrap_or(Move::Up)
+}
+
+fn collided(head: &Coord, body: &[Coord]) -> bool {
+    body.iter().skip(1).any(|body_part| coords_equal(body_part, head))
+}
+
+// Advance the game one turn with every snake's move, returning the snakes eliminated along the way.
+// Eliminated snakes are removed from the board, just as the engine leaves them out of `board.snakes`.
+pub fn step(board: &mut Board, turn: i32, moves: &HashMap<String, Move>, settings: &Settings, rng: &mut impl Rng) -> Vec<Elimination> {
+    // Move every snake, dropping its tail
+    for snake in &mut board.snakes {
+        let move_dir = moves.get(&snake.id).copied().unwrap_or_else(|| default_move(snake));
+        let new_head = simulate_move(&snake.head, &move_dir);
+        snake.body.insert(0, new_head.clone());
+        snake.body.pop();
+        snake.head = new_head;
+    }
+
+    // Starvation and hazards
+    for snake in &mut board.snakes {
+        snake.health -= 1;
+
+        let on_food = board.food.contains(&snake.head);
+        let stacks = board.hazards.iter().filter(|hazard| coords_equal(hazard, &snake.head)).count() as i32;
+        if stacks > 0 && !on_food {
+            snake.health = (snake.health - settings.hazard_damage_per_turn * stacks).clamp(0, SNAKE_MAX_HEALTH);
+        }
+    }
+
+    // Feed snakes, every snake on a piece of food gets to eat it
+    let mut eaten = Vec::new();
+    for food in &board.food {
+        for snake in &mut board.snakes {
+            if coords_equal(&snake.head, food) {
+                snake.health = SNAKE_MAX_HEALTH;
+                let tail = snake.body[snake.body.len() - 1].clone();
+                snake.body.push(tail);
+                eaten.push(food.clone());
+            }
+        }
+    }
+    board.food.retain(|food| !eaten.contains(food));
+
+    if settings.ruleset == Ruleset::Constrictor {
+        for snake in &mut board.snakes {
+            snake.health = SNAKE_MAX_HEALTH;
+            let tail = snake.body[snake.body.len() - 1].clone();
+            snake.body.push(tail);
+        }
+    }
+
+    for snake in &mut board.snakes {
+        snake.length = snake.body.len() as i32;
+    }
+
+    // Food spawns before eliminations, as in the engine, so it never lands where a dying snake still lies
+    if settings.ruleset != Ruleset::Constrictor {
+        let missing = (settings.minimum_food as usize).saturating_sub(board.food.len());
+        if missing > 0 {
+            spawn_food(board, missing, rng);
+        } else if rng.random_range(0..100) < settings.food_spawn_chance {
+            spawn_food(board, 1, rng);
+        }
+    }
+
+    // Eliminations: health and walls first, then collisions between whoever is left
+    let mut eliminations = Vec::new();
+    for snake in &board.snakes {
+        let cause = if snake.health <= 0 {
+            EliminationCause::OutOfHealth
+        } else if snake.head.x < 0 || snake.head.x >= board.width || snake.head.y < 0 || snake.head.y >= board.height {
+            EliminationCause::WallCollision
+        } else {
+            continue;
+        };
+        eliminations.push(Elimination { snake_id: snake.id.clone(), cause, by: None, turn });
+    }
+
+    let survivors: Vec<&Battlesnake> = board
+        .snakes
+        .iter()
+        .filter(|snake| !eliminations.iter().any(|e| e.snake_id == snake.id))
+        .collect();
+    let mut collisions = Vec::new();
+    for snake in &survivors {
+        if collided(&snake.head, &snake.body) {
+            collisions.push(Elimination { snake_id: snake.id.clone(), cause: EliminationCause::SelfCollision, by: None, turn });
+            continue;
+        }
+
+        if let Some(other) = survivors.iter().find(|other| other.id != snake.id && collided(&snake.head, &other.body)) {
+            collisions.push(Elimination {
+                snake_id: snake.id.clone(),
+                cause: EliminationCause::SnakeCollision,
+                by: Some(other.id.clone()),
+                turn,
+            });
+            continue;
+        }
+
+        if let Some(other) = survivors
+            .iter()
+            .find(|other| other.id != snake.id && coords_equal(&snake.head, &other.head) && snake.length <= other.length)
+        {
+            collisions.push(Elimination {
+                snake_id: snake.id.clone(),
+                cause: EliminationCause::HeadCollision,
+                by: Some(other.id.clone()),
+                turn,
+            });
+        }
+    }
+    eliminations.extend(collisions);
+    board.snakes.retain(|snake| !eliminations.iter().any(|e| e.snake_id == snake.id));
+
+    eliminations
+}
+
+// Solo games run until the snake dies, everything else until at most one snake is left
+pub fn is_game_over(board: &Board, settings: &Settings) -> bool {
+    match settings.ruleset {
+        Ruleset::Solo => board.snakes.is_empty(),
+        Ruleset::Standard | Ruleset::Constrictor => board.snakes.len() <= 1,
+    }
+}
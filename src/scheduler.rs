@@ -0,0 +1,86 @@
+// Shares the machine out between all the games we're playing at once, so that a busy server searches
+// less deeply instead of timing out everywhere. Each game gets an even share of the search threads,
+// and the deepest look-ahead it can be expected to finish within its own timeout at that share.
+//
+// How long a depth takes is learned from the game's previous turn: each move deeper costs about the
+// profile's `search_growth` times as much, and half as much of the machine makes it take twice as long.
+
+use std::time::Duration;
+
+use crate::Game;
+use crate::session::GameSession;
+use crate::weights::Weights;
+
+// Shallowest look-ahead we'll fall back to, however busy we are
+pub const MIN_DEPTH: i32 = 2;
+// For games that don't say, the standard timeout
+const DEFAULT_TIMEOUT_MS: u32 = 500;
+
+// How many games are in progress, and how many threads they share
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Load {
+    pub games: usize,
+    pub threads: usize,
+}
+
+impl Load {
+    // An even share of the threads for each game, and always at least one
+    pub fn threads_per_game(&self) -> usize {
+        (self.threads / self.games.max(1)).max(1)
+    }
+
+    // How many threads' worth of CPU time each game gets, less than one when games outnumber threads
+    pub fn share(&self) -> f64 {
+        self.threads.max(1) as f64 / self.games.max(1) as f64
+    }
+}
+
+// How long a game's last look-ahead took, and on what share of the machine
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timing {
+    pub depth: i32,
+    pub elapsed: Duration,
+    pub share: f64,
+}
+
+// How long `game` gives us to answer each request
+pub fn timeout(game: &Game) -> Duration {
+    let timeout = if game.timeout > 0 { game.timeout } else { DEFAULT_TIMEOUT_MS };
+    Duration::from_millis(timeout as u64)
+}
+
+// How long we'll search for on a turn of `game`, the `search_time_fraction` of its timeout
+pub fn budget(game: &Game, weights: &Weights) -> Duration {
+    timeout(game).mul_f64(weights.search_time_fraction)
+}
+
+// The deepest look-ahead, up to the weights' `look_ahead_depth`, that should finish within `budget` at
+// `load`. Without a previous turn to go by, that's `look_ahead_depth`.
+pub fn depth(last: Option<Timing>, load: Load, budget: Duration, weights: &Weights) -> i32 {
+    let max_depth = weights.look_ahead_depth;
+    let Some(last) = last else {
+        return max_depth;
+    };
+
+    let slowdown = last.share / load.share();
+    let expected = |depth: i32| last.elapsed.as_secs_f64() * slowdown * weights.search_growth.powi(depth - last.depth);
+    (MIN_DEPTH..=max_depth)
+        .rev()
+        .find(|&depth| expected(depth) <= budget.as_secs_f64())
+        .unwrap_or(MIN_DEPTH.min(max_depth))
+}
+
+// Sets how many threads and how deep, up to the weights' `look_ahead_depth`, a game's next look-ahead gets
+pub fn plan(session: &mut GameSession, game: &Game, load: Load, weights: &Weights) {
+    session.threads = Some(load.threads_per_game());
+    session.depth = Some(depth(session.timing, load, budget(game, weights), weights));
+}
+
+// Remembers how long the look-ahead `plan` set up took, for the next turn's plan
+pub fn record(session: &mut GameSession, load: Load, elapsed: Duration) {
+    session.timing = session.depth.map(|depth| Timing {
+        depth,
+        elapsed,
+        share: load.share(),
+    });
+}
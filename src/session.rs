@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::opponent::OpponentModel;
+use crate::ponder::Pondering;
+use crate::scheduler::{self, Load, Timing};
+use crate::transposition::TranspositionTable;
+use crate::{Battlesnake, Game, Move};
+
+// Everything we remember about a single game between requests
+#[derive(Debug, Default, Clone)]
+pub struct GameSession {
+    pub opponents: OpponentModel,
+    // How the strategy scored each move it considered on the latest turn, for the recorder
+    pub scores: HashMap<Move, f64>,
+    // How far to look ahead, when something overrides the strategy's own depth
+    pub depth: Option<i32>,
+    // Positions the look-ahead already scored, kept from turn to turn. The opponent model learns as the
+    // game goes on, so a score from an earlier turn reflects what we expected of opponents back then.
+    pub transpositions: Arc<TranspositionTable>,
+    // How many threads the look-ahead may use this turn, one unless the server hands out more
+    pub threads: Option<usize>,
+    // How long the previous turn's look-ahead took, for the scheduler
+    pub timing: Option<Timing>,
+    // Searching ahead on the boards we expect next, until the next request comes in
+    pub pondering: Option<Pondering>,
+}
+
+// A game left without a request for this many of its timeouts is taken to be over, /end or not
+pub const IDLE_TIMEOUTS: u32 = 10;
+
+// A game's session, and when we last had a request for it
+#[derive(Debug)]
+struct Entry {
+    session: Arc<Mutex<GameSession>>,
+    last_seen: Instant,
+    idle_limit: Duration,
+}
+
+impl Entry {
+    fn is_idle(&self, now: Instant) -> bool {
+        now.duration_since(self.last_seen) > self.idle_limit
+    }
+}
+
+// Sessions are keyed by game and by our snake, since two of our snakes can share a game
+#[derive(Debug, Default)]
+pub struct Sessions {
+    games: Mutex<HashMap<String, Entry>>,
+    // Search threads shared out between every game in progress
+    threads: usize,
+}
+
+fn session_key(game: &Game, you: &Battlesnake) -> String {
+    format!("{}:{}", game.id, you.id)
+}
+
+impl Sessions {
+    pub fn with_threads(threads: usize) -> Sessions {
+        Sessions {
+            threads,
+            ..Sessions::default()
+        }
+    }
+
+    pub fn load(&self) -> Load {
+        self.prune();
+        Load {
+            games: self.games.lock().unwrap().len(),
+            threads: self.threads,
+        }
+    }
+
+    // An even share of the search threads for each game in progress, and always at least one
+    pub fn threads_per_game(&self) -> usize {
+        self.load().threads_per_game()
+    }
+
+    // Fetch the session for a game, creating it if we missed /start (e.g. after a restart)
+    pub fn get(&self, game: &Game, you: &Battlesnake) -> Arc<Mutex<GameSession>> {
+        self.prune();
+        let mut games = self.games.lock().unwrap();
+        let entry = games.entry(session_key(game, you)).or_insert_with(|| Entry {
+            session: Arc::default(),
+            last_seen: Instant::now(),
+            idle_limit: scheduler::timeout(game) * IDLE_TIMEOUTS,
+        });
+        entry.last_seen = Instant::now();
+        entry.session.clone()
+    }
+
+    // A copy of a game's session, if we have one, to look at without disturbing the game. It searches
+    // on a table of its own, and leaves the game's pondering to the game.
+    pub fn peek(&self, game: &Game, you: &Battlesnake) -> Option<GameSession> {
+        let games = self.games.lock().unwrap();
+        games.get(&session_key(game, you)).map(|entry| GameSession {
+            transpositions: Arc::default(),
+            pondering: None,
+            ..entry.session.lock().unwrap().clone()
+        })
+    }
+
+    pub fn finish(&self, game: &Game, you: &Battlesnake) -> Option<Arc<Mutex<GameSession>>> {
+        self.games.lock().unwrap().remove(&session_key(game, you)).map(|entry| entry.session)
+    }
+
+    // Forgets the games whose /end never came, stopping anything still pondering on them
+    fn prune(&self) {
+        let now = Instant::now();
+        let mut idle = Vec::new();
+        self.games.lock().unwrap().retain(|_, entry| {
+            let is_idle = entry.is_idle(now);
+            if is_idle {
+                idle.push(entry.session.clone());
+            }
+            !is_idle
+        });
+
+        for session in idle {
+            if let Some(pondering) = session.lock().unwrap().pondering.take() {
+                pondering.stop();
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use crate::opponent::OpponentModel;
+use crate::{Battlesnake, Board, Coord, Move};
+
+pub fn coords_equal(a: &Coord, b: &Coord) -> bool {
+    a.x == b.x && a.y == b.y
+}
+
+// Simulate a move and return the new position
+pub fn simulate_move(head: &Coord, move_dir: &Move) -> Coord {
+    match move_dir {
+        Move::Up => Coord { x: head.x, y: head.y + 1 },
+        Move::Down => Coord { x: head.x, y: head.y - 1 },
+        Move::Left => Coord { x: head.x - 1, y: head.y },
+        Move::Right => Coord { x: head.x + 1, y: head.y },
+    }
+}
+
+// Advance the board by one turn from our point of view: we make `move_dir`, every other snake
+// makes its most likely move, and whoever loses a head-to-head is removed.
+// Anything else that could kill a snake is left to the caller to check.
+pub fn advance(board: &Board, you: &Battlesnake, move_dir: &Move, opponents: &OpponentModel) -> Board {
+    let new_head = simulate_move(&you.head, move_dir);
+
+    // Create a simulated board for the next move
+    let mut simulated_board = Board {
+        height: board.height,
+        width: board.width,
+        food: board.food.clone(),
+        snakes: Vec::new(),
+        hazards: board.hazards.clone(),
+    };
+
+    // Create a simulated you for the next move
+    let mut simulated_you = Battlesnake {
+        id: you.id.clone(),
+        name: you.name.clone(),
+        health: you.health - 1, // Decrease health by 1 each turn
+        body: you.body.clone(),
+        head: new_head.clone(),
+        length: you.length,
+        latency: you.latency.clone(),
+        shout: you.shout.clone(),
+    };
+
+    // Update the head to the new position
+    simulated_you.head = new_head.clone();
+    simulated_you.body.insert(0, new_head.clone());
+
+    // Check if the snake ate food
+    let mut ate_food = false;
+    for (i, food) in board.food.iter().enumerate() {
+        if coords_equal(&new_head, food) {
+            ate_food = true;
+            // Remove food from the simulated board
+            simulated_board.food = board.food.clone();
+            simulated_board.food.remove(i);
+            // Increase health to 100 when eating food
+            simulated_you.health = 100;
+            // Increase length by 1
+            simulated_you.length += 1;
+            break;
+        }
+    }
+
+    // If the snake didn't eat food, remove the tail
+    if !ate_food && simulated_you.body.len() > simulated_you.length as usize {
+        simulated_you.body.pop();
+    }
+
+    // Add the simulated you to the simulated board
+    simulated_board.snakes.push(simulated_you.clone());
+
+    // Simulate other snakes' movements
+    for snake in &board.snakes {
+        if snake.id == you.id {
+            continue; // Skip your own snake, already handled
+        }
+
+        // Other snakes make the move their observed tendencies make most likely.
+        // If there are no safe moves, the snake is trapped
+        let Some(chosen_move) = opponents.most_likely_move(snake, board) else {
+            continue;
+        };
+        let new_head = simulate_move(&snake.head, &chosen_move);
+
+        // Create a simulated snake for the next move
+        let mut simulated_snake = Battlesnake {
+            id: snake.id.clone(),
+            name: snake.name.clone(),
+            health: snake.health - 1, // Decrease health by 1 each turn
+            body: snake.body.clone(),
+            head: new_head.clone(),
+            length: snake.length,
+            latency: snake.latency.clone(),
+            shout: snake.shout.clone(),
+        };
+
+        // Update the head to the new position
+        simulated_snake.head = new_head.clone();
+        simulated_snake.body.insert(0, new_head.clone());
+
+        // Check if the snake ate food
+        let mut ate_food = false;
+        for (i, food) in simulated_board.food.iter().enumerate() {
+            if coords_equal(&new_head, food) {
+                ate_food = true;
+                // Remove food from the simulated board
+                simulated_board.food.remove(i);
+                // Increase health to 100 when eating food
+                simulated_snake.health = 100;
+                // Increase length by 1
+                simulated_snake.length += 1;
+                break;
+            }
+        }
+
+        // If the snake didn't eat food, remove the tail
+        if !ate_food && simulated_snake.body.len() > simulated_snake.length as usize {
+            simulated_snake.body.pop();
+        }
+
+        // Add the simulated snake to the simulated board
+        simulated_board.snakes.push(simulated_snake);
+    }
+
+    // Check for head-to-head collisions
+    let mut head_positions = HashMap::new();
+    for snake in &simulated_board.snakes {
+        let entry = head_positions.entry((snake.head.x, snake.head.y)).or_insert_with(Vec::new);
+        entry.push(snake.clone());
+    }
+
+    // Remove snakes that lost head-to-head collisions
+    let mut snakes_to_remove = Vec::new();
+    for colliding_snakes in head_positions.values() {
+        if colliding_snakes.len() > 1 {
+            // Find the longest snake
+            let mut max_length = 0;
+            for snake in colliding_snakes {
+                if snake.length > max_length {
+                    max_length = snake.length;
+                }
+            }
+
+            // Mark shorter snakes for removal
+            for snake in colliding_snakes {
+                if snake.length < max_length {
+                    snakes_to_remove.push(snake.id.clone());
+                }
+            }
+        }
+    }
+
+    // Remove the marked snakes
+    simulated_board.snakes.retain(|snake| !snakes_to_remove.contains(&snake.id));
+
+    simulated_board
+}
@@ -0,0 +1,185 @@
+// Tunes Rusty's weights by self-play, with simultaneous perturbation stochastic approximation (SPSA).
+//
+// Every iteration nudges all the weights at once, some up and some down at random, and plays a batch
+// of games between the weights nudged one way and the weights nudged the other. Whichever side wins
+// more pulls the weights towards itself. The nudges and the pulls both shrink as the run goes on, the
+// way Spall's original recipe has it, so the weights settle instead of wandering.
+//
+// Weights are whole numbers, so the tuner works on real numbers and rounds them for each game.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::strategy::{Rusty, Strategies};
+use crate::tournament::{self, Score, TournamentConfig};
+use crate::weights::{Profile, Weights};
+
+// How quickly the pull and the nudges shrink, the usual values from Spall
+const ALPHA: f64 = 0.602;
+const GAMMA: f64 = 0.101;
+
+// A weight the tuner adjusts, with the range it keeps to and how far it's nudged at first
+pub struct Parameter {
+    pub name: &'static str,
+    pub min: i32,
+    pub max: i32,
+    pub nudge: f64,
+    pub get: fn(&Weights) -> i32,
+    pub set: fn(&mut Weights, i32),
+}
+
+pub const PARAMETERS: [Parameter; 3] = [
+    Parameter {
+        name: "low_health",
+        min: 0,
+        max: 100,
+        nudge: 8.0,
+        get: |w| w.low_health,
+        set: |w, value| w.low_health = value,
+    },
+    Parameter {
+        name: "look_ahead_depth",
+        min: 2,
+        max: 12,
+        nudge: 1.0,
+        get: |w| w.look_ahead_depth,
+        set: |w, value| w.look_ahead_depth = value,
+    },
+    Parameter {
+        name: "wall_buffer",
+        min: 1,
+        max: 5,
+        nudge: 1.0,
+        get: |w| w.wall_buffer,
+        set: |w, value| w.wall_buffer = value,
+    },
+];
+
+#[derive(Debug, Clone)]
+pub struct SpsaConfig {
+    pub iterations: u32,
+    // How far a fully one-sided batch pulls each weight, in nudges
+    pub learning_rate: f64,
+    // Seeds the nudges
+    pub seed: u64,
+}
+
+impl Default for SpsaConfig {
+    fn default() -> Self {
+        SpsaConfig {
+            iterations: 100,
+            learning_rate: 1.0,
+            seed: 0,
+        }
+    }
+}
+
+// What one iteration played, and where it left the weights
+#[derive(Debug, Clone)]
+pub struct Iteration {
+    pub number: u32,
+    pub plus: Weights,
+    pub minus: Weights,
+    // From `plus`'s point of view
+    pub score: Score,
+    pub weights: Weights,
+}
+
+pub struct Spsa {
+    config: SpsaConfig,
+    start: Weights,
+    theta: Vec<f64>,
+    iteration: u32,
+    rng: StdRng,
+}
+
+impl Spsa {
+    pub fn new(start: &Weights, config: SpsaConfig) -> Spsa {
+        Spsa {
+            theta: PARAMETERS.iter().map(|p| (p.get)(start) as f64).collect(),
+            start: start.clone(),
+            rng: StdRng::seed_from_u64(config.seed),
+            iteration: 0,
+            config,
+        }
+    }
+
+    pub fn iteration(&self) -> u32 {
+        self.iteration
+    }
+
+    pub fn done(&self) -> bool {
+        self.iteration >= self.config.iterations
+    }
+
+    // The weights as tuned so far
+    pub fn weights(&self) -> Weights {
+        self.weights_at(&self.theta)
+    }
+
+    fn weights_at(&self, theta: &[f64]) -> Weights {
+        let mut weights = self.start.clone();
+        for (parameter, value) in PARAMETERS.iter().zip(theta) {
+            (parameter.set)(&mut weights, value.round() as i32);
+        }
+        weights
+    }
+
+    fn clamped(&self, theta: impl Iterator<Item = f64>) -> Vec<f64> {
+        PARAMETERS
+            .iter()
+            .zip(theta)
+            .map(|(p, value)| value.clamp(p.min as f64, p.max as f64))
+            .collect()
+    }
+
+    // Plays one batch through `play`, which scores the first weights it's given against the second
+    pub fn iterate(&mut self, play: impl FnOnce(&Weights, &Weights) -> Result<Score, String>) -> Result<Iteration, String> {
+        self.iteration += 1;
+        let k = self.iteration as f64;
+        let stability = self.config.iterations as f64 / 10.0;
+        let shrink = k.powf(GAMMA);
+        let pull = self.config.learning_rate * (stability + 1.0).powf(ALPHA) / (stability + k).powf(ALPHA);
+
+        let signs: Vec<f64> = PARAMETERS
+            .iter()
+            .map(|_| if self.rng.random::<bool>() { 1.0 } else { -1.0 })
+            .collect();
+        let nudges: Vec<f64> = PARAMETERS.iter().zip(&signs).map(|(p, sign)| sign * p.nudge / shrink).collect();
+
+        let plus = self.weights_at(&self.clamped(self.theta.iter().zip(&nudges).map(|(t, n)| t + n)));
+        let minus = self.weights_at(&self.clamped(self.theta.iter().zip(&nudges).map(|(t, n)| t - n)));
+        let score = play(&plus, &minus)?;
+
+        // From -1 when minus won every game to 1 when plus did
+        let result = if score.games() == 0 { 0.0 } else { 2.0 * score.fraction() - 1.0 };
+        self.theta = self.clamped(self.theta.iter().zip(&nudges).map(|(t, n)| t + pull * result * n));
+
+        Ok(Iteration {
+            number: self.iteration,
+            plus,
+            minus,
+            score,
+            weights: self.weights(),
+        })
+    }
+}
+
+// `profile` with `weights` for the ruleset `config` plays
+pub fn profile_with(profile: &Profile, config: &TournamentConfig, weights: &Weights) -> Profile {
+    let mut profile = profile.clone();
+    profile
+        .rulesets
+        .insert(config.arena.settings.ruleset.as_str().to_string(), weights.clone());
+    profile
+}
+
+// Rusty playing with `first` against Rusty playing with `second`, scored for `first`
+pub fn self_play(config: &TournamentConfig, profile: &Profile, first: &Weights, second: &Weights) -> Result<Score, String> {
+    let strategies = Strategies::default()
+        .register("first", Rusty::new(profile_with(profile, config, first)))
+        .register("second", Rusty::new(profile_with(profile, config, second)));
+    let names = ["first".to_string(), "second".to_string()];
+    let standings = tournament::run(config, &strategies, &names, |_| {})?;
+    Ok(standings.pairings[0].score)
+}
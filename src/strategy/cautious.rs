@@ -0,0 +1,88 @@
+use log::info;
+use serde_json::{json, Value};
+
+use super::Strategy;
+use crate::{Game, GameState, Move};
+use crate::logic;
+use crate::session::GameSession;
+use crate::weights::{Profile, Weights};
+
+// Always takes the move with the best look-ahead safety score, ignoring food and board position.
+// It looks ahead as far as the profile says Rusty does.
+#[derive(Default)]
+pub struct Cautious {
+    profile: Profile,
+}
+
+impl Cautious {
+    pub fn new(profile: Profile) -> Cautious {
+        Cautious { profile }
+    }
+}
+
+impl Strategy for Cautious {
+    fn info(&self) -> Value {
+        json!({
+            "apiversion": "1",
+            "author": "mishagp",
+            "color": "#2f6f8f",
+            "head": "safe",
+            "tail": "round-bum",
+        })
+    }
+
+    fn weights(&self, game: &Game) -> Weights {
+        self.profile.for_game(game).clone()
+    }
+
+    fn get_move(&self, state: &GameState, session: &mut GameSession) -> Value {
+        let (chosen, _) = self.decide(state, session);
+        info!("MOVE {}: {}", state.turn, chosen.as_str());
+        json!({
+            "move": chosen.as_str(),
+        })
+    }
+
+    fn explain(&self, state: &GameState, session: &mut GameSession) -> Value {
+        let (chosen, reason) = self.decide(state, session);
+        json!({
+            "move": chosen.as_str(),
+            "shout": null,
+            "reason": reason,
+            "safety_scores": session.scores,
+        })
+    }
+}
+
+impl Cautious {
+    fn decide(&self, state: &GameState, session: &mut GameSession) -> (Move, &'static str) {
+        let weights = self.profile.for_game(&state.game);
+        let scores = logic::move_safety_scores(
+            &state.board,
+            &state.you,
+            &Move::ALL,
+            session.depth.unwrap_or(weights.look_ahead_depth),
+            weights.head_to_head_risk_limit,
+            &session.opponents,
+            &session.transpositions,
+            session.threads.unwrap_or(1),
+        );
+        // Ties go to the first move, so the same board always gets the same answer
+        let mut safest: Option<(Move, f64)> = None;
+        for move_dir in Move::ALL {
+            let score = scores[&move_dir];
+            if score > 0.0 && safest.is_none_or(|(_, best)| score > best) {
+                safest = Some((move_dir, score));
+            }
+        }
+        session.scores = scores;
+
+        match safest {
+            Some((move_dir, _)) => (move_dir, "the highest safety score"),
+            None => (
+                logic::rank_last_resort_moves(&state.game, &state.board, &state.you, &session.opponents)[0].0,
+                "no safe moves, the best last resort",
+            ),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::cmp::Ordering;
+
+use log::info;
+use serde_json::{Value, json};
+
+use super::Strategy;
+use crate::evaluation::{self, Evaluator};
+use crate::logic;
+use crate::session::GameSession;
+use crate::weights::{Profile, Weights};
+use crate::{Game, GameState, Move};
+
+// Lets an evaluator choose between the moves the look-ahead finds safe enough, in place of Rusty's
+// food and quadrant heuristics. A move is safe enough scoring at least the profile's `safe_enough` share
+// of the safest. It looks ahead as far as the profile says Rusty does, and unless given another
+// evaluator, judges boards with the profile's static evaluation for the game's ruleset.
+pub struct Evaluated {
+    profile: Profile,
+    evaluator: Option<Box<dyn Evaluator>>,
+    info: Value,
+}
+
+impl Evaluated {
+    pub fn new(profile: Profile, info: Value) -> Evaluated {
+        Evaluated {
+            profile,
+            evaluator: None,
+            info,
+        }
+    }
+
+    pub fn with_evaluator(profile: Profile, evaluator: impl Evaluator + 'static, info: Value) -> Evaluated {
+        Evaluated {
+            profile,
+            evaluator: Some(Box::new(evaluator)),
+            info,
+        }
+    }
+
+    fn evaluator(&self, game: &Game) -> &dyn Evaluator {
+        match &self.evaluator {
+            Some(evaluator) => evaluator.as_ref(),
+            None => &self.profile.for_game(game).evaluation,
+        }
+    }
+
+    fn decide(&self, state: &GameState, session: &mut GameSession) -> (Move, Value, &'static str) {
+        let weights = self.profile.for_game(&state.game);
+        let scores = logic::move_safety_scores(
+            &state.board,
+            &state.you,
+            &Move::ALL,
+            session.depth.unwrap_or(weights.look_ahead_depth),
+            weights.head_to_head_risk_limit,
+            &session.opponents,
+            &session.transpositions,
+            session.threads.unwrap_or(1),
+        );
+        let safest = scores.values().copied().fold(0.0, f64::max);
+        session.scores = scores.clone();
+
+        if safest <= 0.0 {
+            let last_resort = logic::rank_last_resort_moves(&state.game, &state.board, &state.you, &session.opponents)[0].0;
+            return (last_resort, Value::Null, "no safe moves, the best last resort");
+        }
+
+        let candidates: Vec<Move> = Move::ALL
+            .into_iter()
+            .filter(|move_dir| scores[move_dir] >= safest * weights.safe_enough)
+            .collect();
+        let evaluator = self.evaluator(&state.game);
+        let chances = evaluation::after_moves(&state.board, &state.you, &candidates, &session.opponents, evaluator);
+
+        // Ties go to the safer move, then to the first, so the same board always gets the same answer
+        let mut best = candidates[0];
+        for &move_dir in &candidates[1..] {
+            let compared = chances[&move_dir]
+                .total_cmp(&chances[&best])
+                .then(scores[&move_dir].total_cmp(&scores[&best]));
+            if compared == Ordering::Greater {
+                best = move_dir;
+            }
+        }
+
+        (best, json!(chances), "the best chance of winning among the safest moves")
+    }
+}
+
+impl Strategy for Evaluated {
+    fn info(&self) -> Value {
+        self.info.clone()
+    }
+
+    fn weights(&self, game: &Game) -> Weights {
+        self.profile.for_game(game).clone()
+    }
+
+    fn get_move(&self, state: &GameState, session: &mut GameSession) -> Value {
+        let (chosen, _, _) = self.decide(state, session);
+        info!("MOVE {}: {}", state.turn, chosen.as_str());
+        json!({
+            "move": chosen.as_str(),
+        })
+    }
+
+    fn explain(&self, state: &GameState, session: &mut GameSession) -> Value {
+        let (chosen, win_chances, reason) = self.decide(state, session);
+        json!({
+            "move": chosen.as_str(),
+            "shout": null,
+            "reason": reason,
+            "safety_scores": session.scores,
+            "win_chances": win_chances,
+        })
+    }
+}
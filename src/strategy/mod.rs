@@ -0,0 +1,120 @@
+use serde_json::{json, Value};
+
+use crate::session::GameSession;
+use crate::weights::{Profile, Weights};
+use crate::{Game, GameState, Move};
+
+mod cautious;
+mod evaluated;
+mod rusty;
+
+pub use cautious::Cautious;
+pub use evaluated::Evaluated;
+pub use rusty::Rusty;
+
+// A Battlesnake's behaviour, one per snake we host.
+// The session has already seen the incoming board by the time a strategy is asked for a move.
+pub trait Strategy: Send + Sync {
+    // Customization returned from the snake's index
+    fn info(&self) -> Value;
+
+    fn start(&self, _state: &GameState, _session: &mut GameSession) {}
+
+    // The weights the strategy plays `game` with, for the server to schedule and ponder by
+    fn weights(&self, _game: &Game) -> Weights {
+        Weights::default()
+    }
+
+    fn get_move(&self, state: &GameState, session: &mut GameSession) -> Value;
+
+    // How the strategy arrived at its move, for /explain. By default that's just the move and its scores.
+    fn explain(&self, state: &GameState, session: &mut GameSession) -> Value {
+        let response = self.get_move(state, session);
+        json!({
+            "move": response.get("move"),
+            "shout": response.get("shout"),
+            "safety_scores": session.scores,
+        })
+    }
+
+    fn end(&self, _state: &GameState, _session: &mut GameSession) {}
+}
+
+// The move in a /move response, if it holds a valid one
+pub fn chosen_move(response: &Value) -> Option<Move> {
+    response.get("move")?.as_str()?.parse().ok()
+}
+
+// Every strategy we serve, by the name it's mounted under. The first one registered also answers at "/".
+#[derive(Default)]
+pub struct Strategies {
+    strategies: Vec<(String, Box<dyn Strategy>)>,
+}
+
+impl Strategies {
+    pub fn register(mut self, name: &str, strategy: impl Strategy + 'static) -> Self {
+        self.strategies.push((name.to_string(), Box::new(strategy)));
+        self
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.strategies.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Strategy> {
+        self.strategies
+            .iter()
+            .find(|(registered, _)| registered == name)
+            .map(|(_, strategy)| strategy.as_ref())
+    }
+
+    // The name of the strategy serving routes mounted at `base`, e.g. "/rusty", with "/" being the default
+    pub fn name_mounted_at(&self, base: &str) -> Option<&str> {
+        match base.trim_matches('/') {
+            "" => self.names().next(),
+            name => self.names().find(|registered| *registered == name),
+        }
+    }
+
+    pub fn mounted_at(&self, base: &str) -> Option<&dyn Strategy> {
+        self.get(self.name_mounted_at(base)?)
+    }
+}
+
+// All the snakes this server hosts, playing with the weights in the profile `Profile::from_env` finds.
+// With the `neural` feature, plus a snake led by the trained network, when there is one.
+pub fn registered() -> Strategies {
+    let profile = Profile::from_env();
+    let strategies = Strategies::default()
+        .register("rusty", Rusty::new(profile.clone()))
+        .register("cautious", Cautious::new(profile.clone()))
+        .register("evaluated", Evaluated::new(profile.clone(), evaluated_info()));
+
+    #[cfg(feature = "neural")]
+    let strategies = match crate::neural::Network::from_env() {
+        Some(network) => strategies.register("neural", Evaluated::with_evaluator(profile, network, neural_info())),
+        None => strategies,
+    };
+    strategies
+}
+
+fn evaluated_info() -> Value {
+    json!({
+        "apiversion": "1",
+        "author": "mishagp",
+        "color": "#c7812a",
+        "head": "evil",
+        "tail": "bolt",
+    })
+}
+
+#[cfg(feature = "neural")]
+fn neural_info() -> Value {
+    json!({
+        "apiversion": "1",
+        "author": "mishagp",
+        "color": "#7a3fa8",
+        "head": "smart-caterpillar",
+        "tail": "curled",
+    })
+}
@@ -0,0 +1,56 @@
+use serde_json::Value;
+
+use super::Strategy;
+use crate::evaluation::Evaluator;
+use crate::logic;
+use crate::session::GameSession;
+use crate::weights::{Profile, Weights};
+use crate::{Game, GameState};
+
+// The original heuristic snake: look-ahead safety, then food, then the emptiest quadrant
+#[derive(Default)]
+pub struct Rusty {
+    profile: Profile,
+}
+
+impl Rusty {
+    pub fn new(profile: Profile) -> Rusty {
+        Rusty { profile }
+    }
+}
+
+impl Strategy for Rusty {
+    fn info(&self) -> Value {
+        logic::info()
+    }
+
+    fn start(&self, state: &GameState, _session: &mut GameSession) {
+        logic::start(&state.game, &state.turn, &state.board, &state.you);
+    }
+
+    fn weights(&self, game: &Game) -> Weights {
+        self.profile.for_game(game).clone()
+    }
+
+    fn get_move(&self, state: &GameState, session: &mut GameSession) -> Value {
+        logic::get_move(
+            &state.game,
+            &state.turn,
+            &state.board,
+            &state.you,
+            session,
+            self.profile.for_game(&state.game),
+        )
+    }
+
+    fn explain(&self, state: &GameState, session: &mut GameSession) -> Value {
+        let weights = self.profile.for_game(&state.game);
+        let mut explanation = logic::explain(&state.game, &state.turn, &state.board, &state.you, session, weights);
+        explanation.trace.win_chance = Some(weights.evaluation.evaluate(&state.board, &state.you));
+        serde_json::to_value(explanation).unwrap_or_default()
+    }
+
+    fn end(&self, state: &GameState, _session: &mut GameSession) {
+        logic::end(&state.game, &state.turn, &state.board, &state.you);
+    }
+}
@@ -0,0 +1,127 @@
+// Texel-style tuning: fits the static evaluation to how recorded games really ended.
+//
+// Every position we were asked to move from in a recording becomes a sample, labelled with how the game
+// ended for us: 1 for a win, 0 for a loss, and a half for a draw. The evaluation's weights are then
+// fitted, by gradient descent, to minimise the mean squared difference between the chance of winning
+// it gives each position and the result. Unlike self-play, this learns from real opponents.
+
+use std::collections::HashMap;
+
+use crate::evaluation::{self, Evaluation, FEATURES};
+use crate::recorder::{Event, Record};
+use crate::{Battlesnake, Board, GameState};
+
+// One recorded position, and how that game ended for the snake that saw it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub features: [f64; FEATURES],
+    pub result: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TexelConfig {
+    pub iterations: u32,
+    pub learning_rate: f64,
+}
+
+impl Default for TexelConfig {
+    fn default() -> Self {
+        TexelConfig {
+            iterations: 2000,
+            learning_rate: 1.0,
+        }
+    }
+}
+
+// How a game ended for `you`, from the board /end sent: a win if only we survived, a loss if only others
+// did, and a draw otherwise. None for a game played alone, which nobody wins.
+pub fn result(board: &Board, you: &Battlesnake, players: usize) -> Option<f64> {
+    if players < 2 {
+        return None;
+    }
+    let we_survived = board.snakes.iter().any(|s| s.id == you.id);
+    let others_survived = board.snakes.iter().any(|s| s.id != you.id);
+    Some(match (we_survived, others_survived) {
+        (true, false) => 1.0,
+        (false, true) => 0.0,
+        _ => 0.5,
+    })
+}
+
+// Every /move of a recorded game that got as far as /end, for each of our snakes in it, with how the
+// game ended for that snake
+pub fn labelled(records: &[Record]) -> Vec<(&GameState, f64)> {
+    let mut moves: HashMap<&str, Vec<&Record>> = HashMap::new();
+    let mut ends: HashMap<&str, &Record> = HashMap::new();
+    for record in records {
+        let you = record.state.you.id.as_str();
+        match record.event {
+            Event::Move => moves.entry(you).or_default().push(record),
+            Event::End => {
+                ends.insert(you, record);
+            }
+            Event::Start => {}
+        }
+    }
+
+    let mut positions = Vec::new();
+    for (you, end) in ends {
+        let Some(recorded) = moves.get(you) else {
+            continue;
+        };
+        let players = recorded.first().map_or(0, |first| first.state.board.snakes.len());
+        let Some(result) = result(&end.state.board, &end.state.you, players) else {
+            continue;
+        };
+        positions.extend(recorded.iter().map(|record| (&record.state, result)));
+    }
+    positions
+}
+
+pub fn samples(records: &[Record]) -> Vec<Sample> {
+    labelled(records)
+        .into_iter()
+        .map(|(state, result)| Sample {
+            features: evaluation::features(&state.board, &state.you),
+            result,
+        })
+        .collect()
+}
+
+// The mean squared difference between the evaluation's chance of winning and the result
+pub fn error(evaluation: &Evaluation, samples: &[Sample]) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let total: f64 = samples
+        .iter()
+        .map(|sample| (evaluation.win_chance(&sample.features) - sample.result).powi(2))
+        .sum();
+    total / samples.len() as f64
+}
+
+// The evaluation fitted to `samples`, starting from `start`
+pub fn tune(start: &Evaluation, samples: &[Sample], config: &TexelConfig) -> Evaluation {
+    let mut coefficients = start.coefficients();
+    if samples.is_empty() {
+        return start.clone();
+    }
+
+    for _ in 0..config.iterations {
+        let evaluation = Evaluation::from_coefficients(coefficients);
+        let mut gradient = [0.0; FEATURES + 1];
+        for sample in samples {
+            let chance = evaluation.win_chance(&sample.features);
+            let slope = 2.0 * (chance - sample.result) * chance * (1.0 - chance);
+            gradient[0] += slope;
+            for (g, feature) in gradient[1..].iter_mut().zip(&sample.features) {
+                *g += slope * feature;
+            }
+        }
+        for (c, g) in coefficients.iter_mut().zip(gradient) {
+            *c -= config.learning_rate * g / samples.len() as f64;
+        }
+    }
+
+    Evaluation::from_coefficients(coefficients)
+}
@@ -0,0 +1,275 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::arena::{self, ArenaConfig, Contestant};
+use crate::strategy::Strategies;
+
+// Many self-play games between strategy versions, summarised as Elo differences,
+// with a sequential probability ratio test to stop as soon as the answer is clear
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Score {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+// An Elo difference with its 95% confidence interval
+#[derive(Debug, Clone, Copy)]
+pub struct EloEstimate {
+    pub difference: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+fn elo_from_score(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+fn score_from_elo(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+impl Score {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    // Points per game, counting a draw as half a win
+    pub fn fraction(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    // Variance of a single game's result
+    fn variance(&self) -> f64 {
+        let s = self.fraction();
+        let n = self.games() as f64;
+        (self.wins as f64 * (1.0 - s).powi(2) + self.draws as f64 * (0.5 - s).powi(2) + self.losses as f64 * s.powi(2)) / n
+    }
+
+    pub fn elo(&self) -> Option<EloEstimate> {
+        if self.games() == 0 {
+            return None;
+        }
+
+        // A clean sweep would be an infinite difference, so keep every score half a game short of one
+        let n = self.games() as f64;
+        let clamped = |score: f64| elo_from_score(score.clamp(0.5 / n, 1.0 - 0.5 / n));
+        let s = self.fraction();
+        let margin = 1.96 * (self.variance() / n).sqrt();
+        Some(EloEstimate {
+            difference: clamped(s),
+            lower: clamped(s - margin),
+            upper: clamped(s + margin),
+        })
+    }
+}
+
+// The Elo difference with its confidence interval, and the games behind it
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.elo() {
+            Some(elo) => write!(
+                f,
+                "{:+.1} Elo [{:+.1}, {:+.1}] (W {} D {} L {})",
+                elo.difference, elo.lower, elo.upper, self.wins, self.draws, self.losses
+            ),
+            None => write!(f, "no games"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SprtOutcome {
+    Continue,
+    // The difference is at most elo0
+    AcceptH0,
+    // The difference is at least elo1
+    AcceptH1,
+}
+
+// Tests H0: Elo difference = elo0 against H1: Elo difference = elo1
+#[derive(Debug, Clone, Copy)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    // Log-likelihood ratio under the normal approximation. One virtual draw is added so the
+    // variance is defined from the first game instead of after the first win and loss.
+    pub fn llr(&self, score: &Score) -> f64 {
+        let regularised = Score {
+            draws: score.draws + 1,
+            ..*score
+        };
+        let (s0, s1) = (score_from_elo(self.elo0), score_from_elo(self.elo1));
+        let variance = regularised.variance();
+        if variance <= 0.0 {
+            return 0.0;
+        }
+
+        regularised.games() as f64 * (s1 - s0) * (2.0 * regularised.fraction() - s0 - s1) / (2.0 * variance)
+    }
+
+    pub fn test(&self, score: &Score) -> SprtOutcome {
+        let (lower, upper) = self.bounds();
+        let llr = self.llr(score);
+        if llr >= upper {
+            SprtOutcome::AcceptH1
+        } else if llr <= lower {
+            SprtOutcome::AcceptH0
+        } else {
+            SprtOutcome::Continue
+        }
+    }
+}
+
+// Results for one pair of strategies, from the first one's point of view
+#[derive(Debug, Clone)]
+pub struct Pairing {
+    pub first: String,
+    pub second: String,
+    pub score: Score,
+}
+
+#[derive(Debug, Clone)]
+pub struct TournamentConfig {
+    // Board and rules for every game, its seed is the base the game seeds count up from
+    pub arena: ArenaConfig,
+    // Games per pairing
+    pub games: u32,
+    pub threads: usize,
+    // Only applied when exactly two strategies play
+    pub sprt: Option<Sprt>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Standings {
+    pub pairings: Vec<Pairing>,
+    pub sprt: Option<SprtOutcome>,
+}
+
+// Play every pair of strategies against each other in 1v1 games, spread across threads.
+// Each seed is played twice with the snakes' starting positions swapped, to cancel out lucky layouts.
+// `progress` is called after every game with the standings so far.
+pub fn run(
+    config: &TournamentConfig,
+    strategies: &Strategies,
+    names: &[String],
+    progress: impl Fn(&Standings) + Sync,
+) -> Result<Standings, String> {
+    let mut pairings = Vec::new();
+    for (i, first) in names.iter().enumerate() {
+        for second in &names[i + 1..] {
+            pairings.push(Pairing {
+                first: first.clone(),
+                second: second.clone(),
+                score: Score::default(),
+            });
+        }
+    }
+    if pairings.is_empty() {
+        return Err("a tournament needs at least two strategies".to_string());
+    }
+    for name in names {
+        if strategies.get(name).is_none() {
+            return Err(format!("unknown strategy: {}", name));
+        }
+    }
+
+    // Jobs cycle through the pairings, so a round robin cut short stays balanced
+    let count = pairings.len() as u32;
+    let jobs = count * config.games;
+    let sprt = if count == 1 { config.sprt } else { None };
+    let standings = Mutex::new(Standings {
+        pairings,
+        sprt: sprt.map(|_| SprtOutcome::Continue),
+    });
+    let next_job = AtomicU32::new(0);
+    let stop = AtomicBool::new(false);
+    let error = Mutex::new(None);
+
+    thread::scope(|scope| {
+        for _ in 0..config.threads.max(1) {
+            scope.spawn(|| {
+                loop {
+                    let job = next_job.fetch_add(1, Ordering::Relaxed);
+                    if job >= jobs || stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+
+                    let (pairing, game) = ((job % count) as usize, job / count);
+                    let (first, second) = {
+                        let standings = standings.lock().unwrap();
+                        let pairing = &standings.pairings[pairing];
+                        (pairing.first.clone(), pairing.second.clone())
+                    };
+
+                    // Self-play between two copies of the same strategy still needs unique snake names
+                    let (first_name, second_name) = if first == second {
+                        (format!("{}-1", first), format!("{}-2", second))
+                    } else {
+                        (first.clone(), second.clone())
+                    };
+                    let mut contestants = vec![
+                        Contestant {
+                            name: first_name.clone(),
+                            strategy: strategies.get(&first).unwrap(),
+                        },
+                        Contestant {
+                            name: second_name,
+                            strategy: strategies.get(&second).unwrap(),
+                        },
+                    ];
+                    if game % 2 == 1 {
+                        contestants.reverse();
+                    }
+
+                    let arena = ArenaConfig {
+                        seed: config.arena.seed.wrapping_add((game / 2) as u64),
+                        ..config.arena.clone()
+                    };
+                    let outcome = match arena::play_game(&arena, &contestants) {
+                        Ok(outcome) => outcome,
+                        Err(e) => {
+                            *error.lock().unwrap() = Some(e);
+                            stop.store(true, Ordering::Relaxed);
+                            break;
+                        }
+                    };
+
+                    let mut standings = standings.lock().unwrap();
+                    let score = &mut standings.pairings[pairing].score;
+                    match outcome.winner {
+                        Some(winner) if winner == first_name => score.wins += 1,
+                        Some(_) => score.losses += 1,
+                        None => score.draws += 1,
+                    }
+
+                    if let Some(sprt) = sprt {
+                        let outcome = sprt.test(&standings.pairings[0].score);
+                        standings.sprt = Some(outcome);
+                        if outcome != SprtOutcome::Continue {
+                            stop.store(true, Ordering::Relaxed);
+                        }
+                    }
+                    progress(&standings);
+                }
+            });
+        }
+    });
+
+    if let Some(e) = error.into_inner().unwrap() {
+        return Err(e);
+    }
+    Ok(standings.into_inner().unwrap())
+}
@@ -0,0 +1,144 @@
+// Zobrist keys for look-ahead positions, and a transposition table remembering what they were worth.
+//
+// A position's key is the XOR of a pseudo-random key for each part of it: every snake's head, each
+// link from one body segment to the next, every snake's length, and each food. Links rather than
+// squares, since the order of a body decides where it goes next. Keys are worked out from what they
+// stand for instead of drawn from a table, so both searches, and every turn, agree on them. Snakes
+// are told apart by id, which keeps them from swapping keys when one of them dies.
+// Health isn't part of a position, since the look-ahead never starves a snake.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{Board, Coord};
+
+pub type Key = u64;
+
+// How many positions a game's table holds, 1 MB worth
+pub const TRANSPOSITION_ENTRIES: usize = 1 << 16;
+
+const HEAD: u64 = 1;
+const LINK: u64 = 2;
+const LENGTH: u64 = 3;
+const FOOD: u64 = 4;
+const DEPTH: u64 = 5;
+
+// The splitmix64 finaliser, which spreads any change in the input over all the bits
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+fn component(snake: u64, kind: u64, (x, y): (i32, i32), extra: u64) -> Key {
+    let packed = kind | (x as u16 as u64) << 8 | (y as u16 as u64) << 24 | extra << 40;
+    mix(snake.wrapping_add(packed.wrapping_mul(0x9e3779b97f4a7c15)))
+}
+
+// What a snake's parts are keyed by, from its id
+pub fn snake(id: &str) -> u64 {
+    // FNV-1a, as std's hashers aren't guaranteed to stay the same between releases
+    let hash = id
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+    mix(hash)
+}
+
+pub fn head(snake: u64, square: (i32, i32)) -> Key {
+    component(snake, HEAD, square, 0)
+}
+
+// From a segment to the one behind it, which is on the same square while a snake is stacked
+pub fn link(snake: u64, from: (i32, i32), to: (i32, i32)) -> Key {
+    let step = ((to.0 - from.0 + 1) * 3 + (to.1 - from.1 + 1)) as u64;
+    component(snake, LINK, from, step)
+}
+
+pub fn length(snake: u64, length: i32) -> Key {
+    component(snake, LENGTH, (0, 0), length as u32 as u64)
+}
+
+pub fn food(square: (i32, i32)) -> Key {
+    component(0, FOOD, square, 0)
+}
+
+// Everything about one snake, head first
+pub fn snake_key(snake: u64, body: impl IntoIterator<Item = (i32, i32)>, body_length: i32) -> Key {
+    let mut body = body.into_iter();
+    let Some(mut previous) = body.next() else {
+        return length(snake, body_length);
+    };
+
+    let mut key = head(snake, previous) ^ length(snake, body_length);
+    for segment in body {
+        key ^= link(snake, previous, segment);
+        previous = segment;
+    }
+    key
+}
+
+pub fn board_key(board: &Board) -> Key {
+    let coords = |coord: &Coord| (coord.x, coord.y);
+    let snakes = board
+        .snakes
+        .iter()
+        .map(|s| snake_key(snake(&s.id), s.body.iter().map(coords), s.length));
+    let food = board.food.iter().map(|f| food(coords(f)));
+    snakes.chain(food).fold(0, |key, part| key ^ part)
+}
+
+// A fixed number of slots, each holding the latest position that hashed to it.
+// Slots are read and written without locking: each keeps its value along with the key XOR the value,
+// so a slot torn by two threads writing at once doesn't match either key and reads as empty.
+pub struct TranspositionTable {
+    slots: Box<[Slot]>,
+}
+
+#[derive(Default)]
+struct Slot {
+    check: AtomicU64,
+    value: AtomicU64,
+}
+
+impl TranspositionTable {
+    // Room for `entries` positions, rounded up to a power of two
+    pub fn new(entries: usize) -> TranspositionTable {
+        let slots = (0..entries.max(1).next_power_of_two()).map(|_| Slot::default()).collect();
+        TranspositionTable { slots }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    // Positions are only the same if they're as far from the end of the look-ahead
+    fn slot(&self, key: Key, depth: i32) -> (Key, &Slot) {
+        let key = key ^ mix(DEPTH | (depth as u32 as u64) << 8);
+        (key, &self.slots[key as usize & (self.slots.len() - 1)])
+    }
+
+    pub fn get(&self, key: Key, depth: i32) -> Option<f64> {
+        let (key, slot) = self.slot(key, depth);
+        let value = slot.value.load(Ordering::Relaxed);
+        (slot.check.load(Ordering::Relaxed) ^ value == key).then(|| f64::from_bits(value))
+    }
+
+    pub fn store(&self, key: Key, depth: i32, value: f64) {
+        let (key, slot) = self.slot(key, depth);
+        let value = value.to_bits();
+        slot.check.store(key ^ value, Ordering::Relaxed);
+        slot.value.store(value, Ordering::Relaxed);
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> TranspositionTable {
+        TranspositionTable::new(TRANSPOSITION_ENTRIES)
+    }
+}
+
+impl fmt::Debug for TranspositionTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TranspositionTable").field("capacity", &self.capacity()).finish()
+    }
+}
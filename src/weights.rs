@@ -0,0 +1,159 @@
+// The weights and thresholds behind get_move's heuristics, read from a TOML profile so they can be
+// tuned without recompiling.
+//
+// A profile has a `[default]` table, plus a table for each ruleset that plays differently, named as in
+// the game's ruleset (`[royale]`, `[constrictor]`, ...). A ruleset's table only needs the weights it
+// changes, the rest come from `[default]`, and anything `[default]` leaves out keeps its built-in value.
+// The static evaluation's weights go in a table of their own, e.g. `[default.evaluation]`, and so do
+// the latency thresholds, in `[default.latency]`.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::Game;
+use crate::evaluation::Evaluation;
+use crate::logic::LOOK_AHEAD_DEPTH;
+use crate::opponent::LatencyThresholds;
+
+// Where the profile is read from, next to Rocket.toml, unless `WEIGHTS` names another file
+pub const DEFAULT_WEIGHTS_PATH: &str = "Weights.toml";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Weights {
+    // Below this much health we go for food even as the longest snake
+    pub low_health: i32,
+    // How many moves the look-ahead searches, and the most the scheduler lets a game search
+    pub look_ahead_depth: i32,
+    // Heading towards a wall is undesirable when the head is fewer than this many squares from it
+    pub wall_buffer: i32,
+    // A longer or equal snake next to a square makes it unsafe once the chance of it moving there
+    // reaches this. Anything we know nothing about has at most three moves, so at a third or less an
+    // unknown opponent always counts.
+    pub head_to_head_risk_limit: f64,
+    // The evaluated snake weighs up every move scoring at least this fraction of the safest one
+    pub safe_enough: f64,
+    // Each extra move of look-ahead costs up to about this many times the one before, for the scheduler
+    pub search_growth: f64,
+    // The part of a game's timeout we spend searching, the rest is for the network and the request itself
+    pub search_time_fraction: f64,
+    // When an opponent counts as close to timing out, see src/opponent.rs
+    pub latency: LatencyThresholds,
+    // The static evaluation, see src/evaluation.rs
+    pub evaluation: Evaluation,
+}
+
+impl Default for Weights {
+    fn default() -> Weights {
+        Weights {
+            low_health: 30,
+            look_ahead_depth: LOOK_AHEAD_DEPTH,
+            wall_buffer: 2,
+            head_to_head_risk_limit: 0.25,
+            safe_enough: 0.5,
+            search_growth: 3.0,
+            search_time_fraction: 0.5,
+            latency: LatencyThresholds::default(),
+            evaluation: Evaluation::default(),
+        }
+    }
+}
+
+// The weights for every ruleset
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profile {
+    pub default: Weights,
+    pub rulesets: HashMap<String, Weights>,
+}
+
+impl Profile {
+    pub fn parse(contents: &str) -> Result<Profile, String> {
+        let mut tables: HashMap<String, toml::Table> = toml::from_str(contents).map_err(|e| e.to_string())?;
+
+        let defaults = tables.remove("default").unwrap_or_default();
+        let default = weights_from(defaults.clone())?;
+        let rulesets = tables
+            .into_iter()
+            .map(|(ruleset, overrides)| {
+                let mut table = defaults.clone();
+                merge(&mut table, overrides);
+                let weights = weights_from(table).map_err(|e| format!("[{}]: {}", ruleset, e))?;
+                Ok((ruleset, weights))
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Profile { default, rulesets })
+    }
+
+    // The profile in the file at `path`, or the built-in weights when there's no such file
+    pub fn load(path: impl AsRef<Path>) -> Result<Profile, String> {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(contents) => Profile::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(_) => Ok(Profile::default()),
+        }
+    }
+
+    // The profile `WEIGHTS` names, or the one next to Rocket.toml. An unreadable profile is ignored.
+    pub fn from_env() -> Profile {
+        let path = env::var("WEIGHTS").unwrap_or_else(|_| DEFAULT_WEIGHTS_PATH.to_string());
+        match Profile::load(&path) {
+            Ok(profile) => {
+                if Path::new(&path).exists() {
+                    info!("Loaded weights for {} rulesets from {}", profile.rulesets.len() + 1, path);
+                }
+                profile
+            }
+            Err(e) => {
+                warn!("Ignoring unreadable weights in {}", e);
+                Profile::default()
+            }
+        }
+    }
+
+    pub fn for_ruleset(&self, ruleset: &str) -> &Weights {
+        self.rulesets.get(ruleset).unwrap_or(&self.default)
+    }
+
+    pub fn for_game(&self, game: &Game) -> &Weights {
+        let ruleset = game.ruleset.get("name").and_then(Value::as_str).unwrap_or_default();
+        self.for_ruleset(ruleset)
+    }
+
+    // The profile as TOML, the way `parse` reads it back
+    pub fn to_toml(&self) -> Result<String, String> {
+        let mut tables = toml::Table::new();
+        let mut add = |name: &str, weights: &Weights| -> Result<(), String> {
+            let table = toml::Table::try_from(weights).map_err(|e| e.to_string())?;
+            tables.insert(name.to_string(), toml::Value::Table(table));
+            Ok(())
+        };
+        add("default", &self.default)?;
+        for (ruleset, weights) in &self.rulesets {
+            add(ruleset, weights)?;
+        }
+        toml::to_string(&tables).map_err(|e| e.to_string())
+    }
+}
+
+// Lays `overrides` over `table`, table by table
+fn merge(table: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (table.get_mut(&key), value) {
+            (Some(toml::Value::Table(inner)), toml::Value::Table(value)) => merge(inner, value),
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+fn weights_from(table: toml::Table) -> Result<Weights, String> {
+    toml::Value::Table(table).try_into().map_err(|e: toml::de::Error| e.message().to_string())
+}
@@ -0,0 +1,188 @@
+use battlesnake_rusty::ascii;
+use battlesnake_rusty::{Coord, GameState};
+
+fn coords(points: &[(i32, i32)]) -> Vec<Coord> {
+    points.iter().map(|&(x, y)| Coord { x, y }).collect()
+}
+
+fn parse(text: &str) -> GameState {
+    ascii::parse(text).unwrap_or_else(|e| panic!("{}\n{}", e, text))
+}
+
+#[test]
+fn parses_snakes_food_and_hazards() {
+    let state = parse(
+        "
+        turn: 12
+        you: B
+        A: health 90
+        # # # # # #
+        # . . * . #
+        # . A a a #
+        # ~ ~ % a #
+        # B b . . #
+        # # # # # #
+        ",
+    );
+
+    assert_eq!(state.turn, 12);
+    assert_eq!((state.board.width, state.board.height), (4, 4));
+    assert_eq!(state.board.food, coords(&[(2, 3), (2, 1)]));
+    assert_eq!(state.board.hazards, coords(&[(0, 1), (1, 1), (2, 1)]));
+
+    let a = &state.board.snakes[0];
+    assert_eq!(a.body, coords(&[(1, 2), (2, 2), (3, 2), (3, 1)]));
+    assert_eq!((a.health, a.length), (90, 4));
+    assert_eq!(state.you.id, "B");
+    assert_eq!(state.you.health, 100);
+    assert_eq!(state.you.body, coords(&[(0, 0), (1, 0)]));
+}
+
+#[test]
+fn follows_coiled_bodies_and_stacks_tails() {
+    let state = parse(
+        "
+        A: length 8
+        ######
+        #aaa.#
+        #aAa.#
+        #....#
+        ######
+        ",
+    );
+
+    let body = &state.you.body;
+    assert_eq!(body.len(), 8);
+    assert_eq!(body[0], Coord { x: 1, y: 1 });
+    for pair in body[..6].windows(2) {
+        assert_eq!((pair[0].x - pair[1].x).abs() + (pair[0].y - pair[1].y).abs(), 1, "{:?}", body);
+    }
+    assert_eq!(body[5], body[6]);
+    assert_eq!(body[6], body[7]);
+}
+
+#[test]
+fn neck_says_which_way_round_a_loop_goes() {
+    let board = "
+        # # # #
+        # a a #
+        # A a #
+        # # # #
+        ";
+    let clockwise = parse(&format!("A: neck up\n{}", board));
+    let anticlockwise = parse(&format!("A: neck right\n{}", board));
+
+    assert_eq!(clockwise.you.body, coords(&[(0, 0), (0, 1), (1, 1), (1, 0)]));
+    assert_eq!(anticlockwise.you.body, coords(&[(0, 0), (1, 0), (1, 1), (0, 1)]));
+    assert_eq!(parse(&ascii::render(&anticlockwise)).you.body, anticlockwise.you.body);
+}
+
+#[test]
+fn rendering_round_trips() {
+    let text = "
+        turn: 40
+        you: A
+        A: health 55, length 6, id gs_a, name rusty
+        B: health 12, length 3, id gs_b, name rival
+        # # # # # # # #
+        # . . . . . * #
+        # . a a a . . #
+        # . a . A ~ ~ #
+        # . a . . % ~ #
+        # . . B b b . #
+        # * . . . . . #
+        # # # # # # # #
+        ";
+    let state = parse(text);
+    let rendered = ascii::render(&state);
+
+    assert_eq!(rendered.trim(), text.lines().map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<_>>().join("\n"));
+    let reparsed = parse(&rendered);
+    assert_eq!(reparsed.you.body, state.you.body);
+    assert_eq!(reparsed.board.snakes[1].body, state.board.snakes[1].body);
+}
+
+#[test]
+fn rejects_broken_boards() {
+    for (text, problem) in [
+        ("# # #\n# . #\n# #", "ragged border"),
+        ("# # #\n# a #\n# # #", "body without a head"),
+        ("# # # #\n# A . a #\n# # # #", "ragged rows"),
+        ("# # # # #\n# A . a #\n# # # # #", "split body"),
+        ("you: B\n# # #\n# A #\n# # #", "missing you"),
+        ("# # #\n# ? #\n# # #", "unknown square"),
+    ] {
+        assert!(ascii::parse(text).is_err(), "accepted {}", problem);
+    }
+}
+
+// Every way a snake of `length` can lie without crossing itself, from `path` on, inside a `size` square
+fn walks(path: &mut Vec<Coord>, length: usize, size: i32, found: &mut Vec<Vec<Coord>>) {
+    if path.len() == length {
+        found.push(path.clone());
+        return;
+    }
+    let last = path.last().unwrap().clone();
+    for (dx, dy) in [(0, 1), (0, -1), (-1, 0), (1, 0)] {
+        let next = Coord { x: last.x + dx, y: last.y + dy };
+        if next.x >= 0 && next.y >= 0 && next.x < size && next.y < size && !path.contains(&next) {
+            path.push(next);
+            walks(path, length, size, found);
+            path.pop();
+        }
+    }
+}
+
+#[test]
+fn coiled_snakes_round_trip_however_they_lie() {
+    let mut state = parse(
+        "
+        # # # # #
+        # . . . #
+        # . . . #
+        # A . . #
+        # # # # #
+        ",
+    );
+
+    for length in 4..=9 {
+        let mut bodies = Vec::new();
+        for x in 0..3 {
+            for y in 0..3 {
+                walks(&mut vec![Coord { x, y }], length, 3, &mut bodies);
+            }
+        }
+
+        for body in bodies {
+            let snake = &mut state.board.snakes[0];
+            snake.head = body[0].clone();
+            snake.body = body;
+            snake.length = snake.body.len() as i32;
+            state.you = snake.clone();
+
+            let rendered = ascii::render(&state);
+            assert_eq!(parse(&rendered).you.body, state.you.body, "\n{}", rendered);
+        }
+    }
+}
+
+#[test]
+fn quotes_ids_and_names_that_would_not_parse() {
+    let mut state = parse(
+        "
+        # # # #
+        # A a #
+        # # # #
+        ",
+    );
+    for (id, name) in [("gs_1,2", "rusty, the snake"), ("\"quoted\"", " padded "), ("plain", "two words")] {
+        state.board.snakes[0].id = id.to_string();
+        state.board.snakes[0].name = name.to_string();
+        state.you = state.board.snakes[0].clone();
+
+        let rendered = ascii::render(&state);
+        let reparsed = parse(&rendered);
+        assert_eq!((reparsed.you.id.as_str(), reparsed.you.name.as_str()), (id, name), "\n{}", rendered);
+    }
+    assert!(ascii::render(&state).contains("name two words\n"));
+}
@@ -0,0 +1,39 @@
+use battlesnake_rusty::session::GameSession;
+use battlesnake_rusty::weights::Weights;
+use battlesnake_rusty::{GameState, ascii};
+
+// Helpers shared by the search tests, and by the search benchmark
+
+// A busy 11x11 board: four snakes of different lengths, with food spread around
+pub fn four_snakes() -> GameState {
+    ascii::parse(
+        "
+        # # # # # # # # # # # # #
+        # . . . . . . . . . . . #
+        # . . * . . . . . . . . #
+        # . . . . . b b b . . . #
+        # . . . . . . . B . . . #
+        # . . a a a . . . . * . #
+        # . . a . A . . . . . . #
+        # . . . . . . . . . . . #
+        # . . C c c . . . . . . #
+        # . . . . c . . . D d . #
+        # . * . . . . . . . d . #
+        # . . . . . . . . . d . #
+        # # # # # # # # # # # # #
+        ",
+    )
+    .unwrap()
+}
+
+// A fresh session for the game, which has seen `state` as the server would on a move request
+pub fn session(state: &GameState) -> GameSession {
+    let mut session = GameSession::default();
+    session.opponents.observe(&state.game, &state.board, &state.you);
+    session
+}
+
+// The head-to-head risk limit the built-in weights search with
+pub fn risk_limit() -> f64 {
+    Weights::default().head_to_head_risk_limit
+}
@@ -0,0 +1,106 @@
+use std::fs;
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use battlesnake_rusty::arena::{self, ArenaConfig, Contestant};
+use battlesnake_rusty::remote::RemoteSnake;
+use battlesnake_rusty::strategy::Strategy;
+
+// Full games refereed over HTTP against the real Rocket server
+
+struct Server {
+    child: Child,
+    port: u16,
+    profiles: PathBuf,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        // Killed mid-save, the server may also leave its temporary file behind
+        let _ = fs::remove_file(&self.profiles);
+        let _ = fs::remove_file(self.profiles.with_extension("json.tmp"));
+    }
+}
+
+fn start_server() -> Server {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let profiles = std::env::temp_dir().join(format!("opponent_profiles_{}.json", port));
+    let child = Command::new(env!("CARGO_BIN_EXE_battlesnake-rusty"))
+        .env("PORT", port.to_string())
+        .env("ROCKET_ADDRESS", "127.0.0.1")
+        .env("OPPONENT_PROFILES", &profiles)
+        .env("RUST_LOG", "warn")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let server = Server { child, port, profiles };
+
+    let started = Instant::now();
+    while TcpStream::connect(("127.0.0.1", port)).is_err() {
+        assert!(started.elapsed() < Duration::from_secs(30), "server didn't start");
+        thread::sleep(Duration::from_millis(50));
+    }
+    server
+}
+
+#[test]
+fn index_answers_for_every_hosted_snake() {
+    let server = start_server();
+
+    for prefix in ["", "/rusty", "/cautious", "/evaluated"] {
+        let snake = RemoteSnake::new(&format!("http://127.0.0.1:{}{}", server.port, prefix)).unwrap();
+        assert_eq!(snake.info()["apiversion"], "1", "index at {:?}", prefix);
+    }
+}
+
+#[test]
+fn plays_a_game_over_http() {
+    let server = start_server();
+    let rusty = RemoteSnake::new(&format!("http://127.0.0.1:{}/rusty", server.port)).unwrap();
+    let cautious = RemoteSnake::new(&format!("http://127.0.0.1:{}/cautious", server.port)).unwrap();
+
+    // Debug builds are slow, so be generous with the timeout and keep the game short
+    let config = ArenaConfig {
+        timeout: 5000,
+        max_turns: Some(20),
+        seed: 7,
+        ..ArenaConfig::default()
+    };
+    let contestants = [
+        Contestant {
+            name: "rusty".to_string(),
+            strategy: &rusty,
+        },
+        Contestant {
+            name: "cautious".to_string(),
+            strategy: &cautious,
+        },
+    ];
+
+    let outcome = arena::play_game(&config, &contestants).unwrap();
+    assert!(outcome.turns > 0);
+    // Both snakes answer every turn, so nobody blunders into a wall on the default move this early
+    assert!(
+        outcome.eliminations.iter().all(|e| e.turn > 3),
+        "unexpected early eliminations: {:?}",
+        outcome.eliminations
+    );
+}
+
+#[test]
+fn unreachable_snake_is_rejected_before_the_game() {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let missing = RemoteSnake::new(&format!("http://127.0.0.1:{}", port)).unwrap();
+    let contestants = [Contestant {
+        name: "missing".to_string(),
+        strategy: &missing,
+    }];
+
+    assert!(arena::play_game(&ArenaConfig::default(), &contestants).is_err());
+}
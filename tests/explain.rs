@@ -0,0 +1,53 @@
+use battlesnake_rusty::session::GameSession;
+use battlesnake_rusty::weights::Weights;
+use battlesnake_rusty::{GameState, Move, ascii, logic};
+
+fn explain(board: &str) -> logic::Explanation {
+    let state: GameState = ascii::parse(board).unwrap();
+    let mut session = GameSession::default();
+    session.opponents.observe(&state.game, &state.board, &state.you);
+    logic::explain(&state.game, &state.turn, &state.board, &state.you, &mut session, &Weights::default())
+}
+
+#[test]
+fn explains_the_only_safe_move() {
+    let explanation = explain(
+        "
+        # # # # #
+        # A a . #
+        # . a . #
+        # . . . #
+        # # # # #
+        ",
+    );
+
+    assert_eq!(explanation.move_dir, Move::Down);
+    assert_eq!(explanation.reason, "the only safe move");
+    assert!(explanation.trace.is_move_safe[&Move::Down]);
+    assert!(!explanation.trace.is_move_safe[&Move::Right]);
+    assert!(explanation.trace.safety_scores[&Move::Down] > 0.0);
+    assert!(explanation.trace.last_resort.is_empty());
+    assert_eq!(explanation.response()["move"], "down");
+}
+
+#[test]
+fn explains_a_last_resort() {
+    let explanation = explain(
+        "
+        A: length 4
+        # # # #
+        # A a #
+        # b a #
+        # B a #
+        # # # #
+        ",
+    );
+
+    assert!(explanation.reason.starts_with("no safe moves"), "{}", explanation.reason);
+    assert_eq!(explanation.trace.last_resort.len(), 4);
+    assert!(explanation.trace.is_move_safe.values().all(|safe| !safe));
+
+    let value = serde_json::to_value(&explanation).unwrap();
+    assert_eq!(value["move"], explanation.move_dir.as_str());
+    assert!(value["last_resort"][0]["kind"].is_string());
+}
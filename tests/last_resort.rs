@@ -0,0 +1,97 @@
+use battlesnake_rusty::logic::{self, LastResort};
+use battlesnake_rusty::opponent::OpponentModel;
+use battlesnake_rusty::{GameState, Move, ascii};
+
+fn ranked(board: &str) -> Vec<(Move, LastResort, f64)> {
+    let state: GameState = ascii::parse(board).unwrap();
+    logic::rank_last_resort_moves(&state.game, &state.board, &state.you, &OpponentModel::default())
+}
+
+#[test]
+fn a_winnable_head_to_head_beats_a_tail() {
+    // Up meets B, which is shorter than us; Left is C's tail
+    let ranked = ranked(
+        "
+        # # # # # # #
+        # C . B b . #
+        # c . . . . #
+        # c c A e E #
+        # . . a e . #
+        # . . a . . #
+        # # # # # # #
+        ",
+    );
+
+    assert_eq!(ranked[0], (Move::Up, LastResort::HeadToHead, 1.0));
+    assert_eq!(ranked[1], (Move::Left, LastResort::Tail, 1.0));
+    assert!(ranked[2..].iter().all(|(_, kind, survival)| *kind == LastResort::Fatal && *survival == 0.0));
+}
+
+#[test]
+fn a_tail_beats_a_hazard() {
+    let ranked = ranked(
+        "
+        # # # # # # #
+        # C . . . . #
+        # c . ~ . . #
+        # c c A e E #
+        # . . a e . #
+        # . . a . . #
+        # # # # # # #
+        ",
+    );
+
+    assert_eq!(ranked[0], (Move::Left, LastResort::Tail, 1.0));
+    assert_eq!(ranked[1], (Move::Up, LastResort::Hazard, 1.0));
+}
+
+#[test]
+fn a_hazard_beats_a_wall() {
+    let ranked = ranked(
+        "
+        # # # # #
+        # . . . #
+        # ~ . . #
+        # A a a #
+        # # # # #
+        ",
+    );
+
+    assert_eq!(ranked[0], (Move::Up, LastResort::Hazard, 1.0));
+    let down = ranked.iter().find(|(move_dir, _, _)| *move_dir == Move::Down).unwrap();
+    assert_eq!(*down, (Move::Down, LastResort::Fatal, 0.0));
+}
+
+#[test]
+fn the_odds_of_surviving_come_before_the_kind_of_gamble() {
+    // B is as long as us and might take the square, so the hazard is the better bet
+    let odds = ranked(
+        "
+        A: health 50
+        # # # # # # #
+        # . . B b b #
+        # . . . . . #
+        # . ~ A a a #
+        # # # # # # #
+        ",
+    );
+
+    assert_eq!(odds[0].0, Move::Left);
+    assert_eq!(odds[0].1, LastResort::Hazard);
+    let up = odds.iter().find(|(move_dir, _, _)| *move_dir == Move::Up).unwrap();
+    assert_eq!(up.1, LastResort::HeadToHead);
+    assert!(up.2 > 0.0 && up.2 < 1.0, "{:?}", up);
+    // A hazard that would finish us off is no better than a wall
+    let hopeless = ranked(
+        "
+        A: health 10
+        # # # # # # #
+        # . . B b b #
+        # . . . . . #
+        # . ~ A a a #
+        # # # # # # #
+        ",
+    );
+    let left = hopeless.iter().find(|(move_dir, _, _)| *move_dir == Move::Left).unwrap();
+    assert_eq!(*left, (Move::Left, LastResort::Hazard, 0.0));
+}
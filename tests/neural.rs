@@ -0,0 +1,122 @@
+use battlesnake_rusty::session::GameSession;
+use battlesnake_rusty::strategy::{Evaluated, Strategy, chosen_move};
+use battlesnake_rusty::weights::Profile;
+use battlesnake_rusty::{GameState, Move, ascii};
+use serde_json::json;
+
+fn board(text: &str) -> GameState {
+    ascii::parse(text).unwrap()
+}
+
+#[test]
+fn evaluated_snakes_keep_to_the_safe_moves() {
+    // Left is a wall, Right and Down are bodies
+    let state = board(
+        "
+        # # # # # # #
+        # . b . . . #
+        # A b . . . #
+        # a B . . . #
+        # a . . . . #
+        # # # # # # #
+        ",
+    );
+    let strategy = Evaluated::new(Profile::default(), json!({ "apiversion": "1" }));
+    let mut session = GameSession::default();
+    session.opponents.observe(&state.game, &state.board, &state.you);
+
+    let explanation = strategy.explain(&state, &mut session);
+    assert_eq!(chosen_move(&explanation), Some(Move::Up));
+    assert_eq!(explanation["win_chances"].as_object().unwrap().len(), 1);
+}
+
+#[cfg(feature = "neural")]
+mod network {
+    use super::board;
+    use battlesnake_rusty::arena::ArenaConfig;
+    use battlesnake_rusty::evaluation::{Evaluator, FEATURES};
+    use battlesnake_rusty::neural::{self, Example, INPUTS, Network, PLANES, VIEW};
+    use battlesnake_rusty::strategy;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    const SIDE: usize = (2 * VIEW + 1) as usize;
+
+    #[test]
+    fn sees_the_board_from_our_head() {
+        let state = board(
+            "
+            # # # # #
+            # . . * #
+            # . . . #
+            # A a a #
+            # # # # #
+            ",
+        );
+        let inputs = neural::inputs(&state.board, &state.you);
+        assert_eq!(inputs.len(), INPUTS);
+
+        let plane = |plane: usize, dx: i32, dy: i32| {
+            inputs[(plane * SIDE + (dy + VIEW) as usize) * SIDE + (dx + VIEW) as usize]
+        };
+        // Off the board, then our body, then food
+        assert_eq!(plane(0, -1, 0), 1.0);
+        assert_eq!(plane(0, 0, 0), 0.0);
+        assert_eq!(plane(1, 1, 0), 1.0);
+        assert_eq!(plane(5, 2, 2), 1.0);
+        assert_eq!(inputs[..PLANES * SIDE * SIDE].iter().filter(|&&input| input != 0.0).count(), 121 - 9 + 2 + 1);
+        assert_eq!(inputs.len() - PLANES * SIDE * SIDE, FEATURES);
+    }
+
+    #[test]
+    fn learns_what_wins_and_reads_back_the_same() {
+        // Games were won whenever there was food right next to the head
+        let examples: Vec<Example> = (0..200)
+            .map(|i| {
+                let mut inputs = vec![0.0; INPUTS];
+                let food = i % 2 == 0;
+                if food {
+                    inputs[(5 * SIDE + VIEW as usize) * SIDE + VIEW as usize + 1] = 1.0;
+                }
+                inputs[(i * 7) % INPUTS] = 1.0;
+                Example {
+                    inputs,
+                    result: if food { 1.0 } else { 0.0 },
+                }
+            })
+            .collect();
+
+        let mut network = Network::new(8, 1);
+        let untrained = network.error(&examples);
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..20 {
+            network.train_epoch(&examples, 0.05, &mut rng);
+        }
+        assert!(network.error(&examples) < untrained / 4.0, "{} then {}", untrained, network.error(&examples));
+
+        let path = std::env::temp_dir().join(format!("network-{}.json", std::process::id()));
+        network.save(&path).unwrap();
+        let loaded = Network::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, network);
+    }
+
+    #[test]
+    fn exports_self_play_positions_with_their_results() {
+        let config = ArenaConfig {
+            width: 7,
+            height: 7,
+            max_turns: Some(15),
+            seed: 5,
+            ..ArenaConfig::default()
+        };
+        let names = ["cautious".to_string(), "cautious".to_string()];
+        let samples = neural::from_self_play(&config, &strategy::registered(), &names, 2, 2).unwrap();
+
+        assert!(!samples.is_empty());
+        assert!(samples.iter().all(|s| [0.0, 0.5, 1.0].contains(&s.result)));
+        let network = Network::new(4, 0);
+        let chance = network.evaluate(&samples[0].state.board, &samples[0].state.you);
+        assert!((0.0..=1.0).contains(&chance));
+    }
+}
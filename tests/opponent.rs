@@ -0,0 +1,207 @@
+use battlesnake_rusty::opponent::{LatencyThresholds, OpponentModel, Responsiveness, Tendency};
+use battlesnake_rusty::{Battlesnake, Coord, GameState, Move, ascii};
+
+fn state(board: &str) -> GameState {
+    ascii::parse(board).unwrap()
+}
+
+// B heads up onto the square next to the food, passing up a move along the bottom wall
+fn before() -> GameState {
+    state(
+        "
+        # # # # # # #
+        # A a . . . #
+        # . . . . . #
+        # . . * . . #
+        # . . . . . #
+        # b b B . . #
+        # # # # # # #
+        ",
+    )
+}
+
+fn after() -> GameState {
+    state(
+        "
+        # # # # # # #
+        # A a . . . #
+        # . . . . . #
+        # . . * . . #
+        # . . B . . #
+        # . b b . . #
+        # # # # # # #
+        ",
+    )
+}
+
+fn observed(states: &[GameState]) -> OpponentModel {
+    let mut model = OpponentModel::default();
+    for state in states {
+        model.observe(&state.game, &state.board, &state.you);
+    }
+    model
+}
+
+fn snake<'a>(state: &'a GameState, id: &str) -> &'a Battlesnake {
+    state.board.snakes.iter().find(|s| s.id == id).unwrap()
+}
+
+#[test]
+fn tendencies_are_laplace_smoothed() {
+    assert_eq!(Tendency::default().probability(), 0.5);
+
+    let tendency: Tendency = serde_json::from_str(r#"{"chosen": 3, "available": 4}"#).unwrap();
+    assert_eq!(tendency.probability(), 4.0 / 6.0);
+    let never: Tendency = serde_json::from_str(r#"{"chosen": 0, "available": 8}"#).unwrap();
+    assert_eq!(never.probability(), 0.1);
+}
+
+#[test]
+fn observes_the_traits_an_opponent_chose() {
+    let model = observed(&[before(), after()]);
+
+    let (id, name, profile) = model.observed().find(|(id, _, _)| *id == "B").unwrap();
+    assert_eq!((id, name), ("B", "B"));
+    // Up got closer to the food and Right hugged the wall, so it chose one and passed up the other
+    assert_eq!(profile.food_greed.probability(), 2.0 / 3.0);
+    assert_eq!(profile.wall_hugging.probability(), 1.0 / 3.0);
+    // Neither move went near a head or towards its tail, so those stay unknown
+    assert_eq!(profile.head_to_head.probability(), 0.5);
+    assert_eq!(profile.tail_chasing.probability(), 0.5);
+    // We only profile opponents
+    assert!(model.observed().all(|(id, _, _)| id != "A"));
+}
+
+#[test]
+fn a_single_board_teaches_nothing() {
+    let model = observed(&[after()]);
+    let (_, _, profile) = model.observed().find(|(id, _, _)| *id == "B").unwrap();
+    assert_eq!(profile.food_greed.probability(), 0.5);
+}
+
+#[test]
+fn unknown_snakes_move_uniformly() {
+    let state = after();
+    let probabilities = OpponentModel::default().move_probabilities(snake(&state, "B"), &state.board);
+
+    // Down runs into its own body
+    let moves: Vec<Move> = probabilities.iter().map(|(move_dir, _)| *move_dir).collect();
+    assert_eq!(moves, [Move::Up, Move::Left, Move::Right]);
+    for (_, probability) in probabilities {
+        assert!((probability - 1.0 / 3.0).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn predicts_the_tendencies_it_saw() {
+    let model = observed(&[before(), after()]);
+    let state = after();
+    let b = snake(&state, "B");
+
+    let probabilities = model.move_probabilities(b, &state.board);
+    let total: f64 = probabilities.iter().map(|(_, p)| p).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+
+    let up = probabilities.iter().find(|(move_dir, _)| *move_dir == Move::Up).unwrap().1;
+    assert!(probabilities.iter().all(|(_, p)| *p <= up), "{:?}", probabilities);
+    assert_eq!(model.most_likely_move(b, &state.board), Some(Move::Up));
+}
+
+#[test]
+fn head_to_head_risk_is_the_chance_a_longer_snake_takes_the_square() {
+    let model = observed(&[before(), after()]);
+    let state = after();
+    let up = model
+        .move_probabilities(snake(&state, "B"), &state.board)
+        .into_iter()
+        .find(|(move_dir, _)| *move_dir == Move::Up)
+        .unwrap()
+        .1;
+
+    // A is shorter than B, so B wins if it goes there too
+    let food = Coord { x: 2, y: 2 };
+    assert!((model.head_to_head_risk(&food, &state.board, &state.you) - up).abs() < 1e-9);
+    // Out of B's reach
+    assert_eq!(model.head_to_head_risk(&Coord { x: 4, y: 4 }, &state.board, &state.you), 0.0);
+
+    // A longer A would win, so B is no threat
+    let mut longer = state.you.clone();
+    longer.length = 4;
+    assert_eq!(model.head_to_head_risk(&food, &state.board, &longer), 0.0);
+}
+
+// B's responsiveness once it has reported each of `latencies` in turn, with a 500ms timeout unless
+// `timeout` says otherwise
+fn responsiveness(latencies: &[u32], timeout: u32) -> Responsiveness {
+    let mut model = OpponentModel::default();
+    let mut state = after();
+    state.game.timeout = timeout;
+    for latency in latencies {
+        state.board.snakes[1].latency = latency.to_string();
+        model.observe(&state.game, &state.board, &state.you);
+    }
+    model.responsiveness("B")
+}
+
+#[test]
+fn flags_snakes_that_time_out() {
+    assert_eq!(responsiveness(&[], 500), Responsiveness::Responsive);
+    assert_eq!(responsiveness(&[100, 120, 110], 500), Responsiveness::Responsive);
+    assert_eq!(responsiveness(&[100, 500], 500), Responsiveness::TimedOut);
+    // Answering in time again clears it
+    assert_eq!(responsiveness(&[500, 100], 500), Responsiveness::Responsive);
+}
+
+#[test]
+fn projects_the_latency_trend_towards_the_timeout() {
+    // Still under 80% of the timeout, but rising 50ms a turn
+    assert_eq!(responsiveness(&[200, 250, 300, 350], 500), Responsiveness::NearTimeout);
+    assert_eq!(responsiveness(&[350, 350, 350, 350], 500), Responsiveness::Responsive);
+    assert_eq!(responsiveness(&[410], 500), Responsiveness::NearTimeout);
+}
+
+#[test]
+fn only_the_latest_latencies_count() {
+    // Across all six the slope would be 70ms a turn, but the fast first answer has left the window
+    assert_eq!(responsiveness(&[0, 350, 350, 350, 350, 350], 500), Responsiveness::Responsive);
+    assert_eq!(responsiveness(&[0, 350, 350, 350, 350], 500), Responsiveness::NearTimeout);
+}
+
+#[test]
+fn the_profile_sets_the_latency_thresholds() {
+    let judged = |window, near_timeout_fraction| {
+        let mut model = OpponentModel::default();
+        model.set_latency_thresholds(LatencyThresholds { window, near_timeout_fraction });
+        let mut state = after();
+        for latency in [0, 320, 320] {
+            state.board.snakes[1].latency = latency.to_string();
+            model.observe(&state.game, &state.board, &state.you);
+        }
+        model.responsiveness("B")
+    };
+    // Rising 160ms a turn over all three, but flat over the last two
+    assert_eq!(responsiveness(&[0, 320, 320], 500), Responsiveness::NearTimeout);
+    assert_eq!(judged(2, 0.9), Responsiveness::Responsive);
+    assert_eq!(judged(2, 0.6), Responsiveness::NearTimeout);
+}
+
+#[test]
+fn without_a_timeout_nobody_is_near_it() {
+    assert_eq!(responsiveness(&[100, 200, 300], 0), Responsiveness::Responsive);
+}
+
+#[test]
+fn snakes_near_the_timeout_are_likely_to_repeat_their_move() {
+    let mut model = OpponentModel::default();
+    let (mut before, mut after) = (before(), after());
+    before.board.snakes[1].latency = "300".to_string();
+    after.board.snakes[1].latency = "420".to_string();
+    model.observe(&before.game, &before.board, &before.you);
+    model.observe(&after.game, &after.board, &after.you);
+    assert_eq!(model.responsiveness("B"), Responsiveness::NearTimeout);
+
+    // It went up last turn, and going up again is half the prediction on top of its usual share
+    let probabilities = model.move_probabilities(&after.board.snakes[1], &after.board);
+    let up = probabilities.iter().find(|(move_dir, _)| *move_dir == Move::Up).unwrap().1;
+    assert!(up > 0.5, "{:?}", probabilities);
+}
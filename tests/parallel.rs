@@ -0,0 +1,83 @@
+use battlesnake_rusty::session::{IDLE_TIMEOUTS, Sessions};
+use battlesnake_rusty::transposition::TranspositionTable;
+use battlesnake_rusty::{Game, Move, ascii, logic};
+
+mod common;
+
+#[test]
+fn threads_find_the_same_scores() {
+    let state = common::four_snakes();
+    let session = common::session(&state);
+
+    // Each on a fresh table, so no run gets to reuse another's work
+    let scores = |threads| {
+        let table = TranspositionTable::new(1 << 12);
+        logic::move_safety_scores(&state.board, &state.you, &Move::ALL, 6, common::risk_limit(), &session.opponents, &table, threads)
+    };
+    let alone = scores(1);
+    assert_eq!(alone.len(), 4);
+    for threads in [2, 3, 4, 8] {
+        assert_eq!(scores(threads), alone, "on {} threads", threads);
+    }
+}
+
+#[test]
+fn games_share_the_threads() {
+    let state = ascii::parse(
+        "
+        # # # # #
+        # A a . #
+        # . . . #
+        # # # # #
+        ",
+    )
+    .unwrap();
+    let game = |id: &str| Game {
+        id: id.to_string(),
+        ..state.game.clone()
+    };
+    let sessions = Sessions::with_threads(8);
+    assert_eq!(sessions.threads_per_game(), 8);
+
+    sessions.get(&game("one"), &state.you);
+    sessions.get(&game("two"), &state.you);
+    sessions.get(&game("three"), &state.you);
+    assert_eq!(sessions.threads_per_game(), 2);
+
+    sessions.finish(&game("three"), &state.you);
+    assert_eq!(sessions.threads_per_game(), 4);
+
+    // Never none, however many games there are
+    assert_eq!(Sessions::with_threads(1).threads_per_game(), 1);
+    assert_eq!(Sessions::default().threads_per_game(), 1);
+}
+
+#[test]
+fn abandoned_games_stop_counting() {
+    let state = ascii::parse(
+        "
+        # # # # #
+        # A a . #
+        # . . . #
+        # # # # #
+        ",
+    )
+    .unwrap();
+    let game = |id: &str, timeout: u32| Game {
+        id: id.to_string(),
+        timeout,
+        ..state.game.clone()
+    };
+    let sessions = Sessions::with_threads(8);
+
+    // One whose /end never comes, and one still being played
+    sessions.get(&game("abandoned", 5), &state.you);
+    sessions.get(&game("playing", 500), &state.you);
+    assert_eq!(sessions.load().games, 2);
+
+    std::thread::sleep(std::time::Duration::from_millis(5 * IDLE_TIMEOUTS as u64 + 20));
+    assert_eq!(sessions.load().games, 1);
+    assert_eq!(sessions.threads_per_game(), 8);
+    assert!(sessions.finish(&game("abandoned", 5), &state.you).is_none());
+    assert!(sessions.finish(&game("playing", 500), &state.you).is_some());
+}
@@ -0,0 +1,60 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use battlesnake_rusty::bitboard::Search;
+use battlesnake_rusty::session::GameSession;
+use battlesnake_rusty::transposition::TranspositionTable;
+use battlesnake_rusty::{GameState, Move, logic, ponder, simulation};
+
+mod common;
+
+fn session(state: &GameState, depth: i32) -> GameSession {
+    GameSession {
+        depth: Some(depth),
+        ..common::session(state)
+    }
+}
+
+#[test]
+fn pondering_does_the_next_search_in_advance() {
+    let state = common::four_snakes();
+    let session = session(&state, 6);
+    ponder::start(&state, Move::Left, &session, common::risk_limit()).unwrap().wait();
+
+    // The board if every opponent makes its likeliest reply, which is pondered first
+    let board = simulation::advance(&state.board, &state.you, &Move::Left, &session.opponents);
+    let you = board.snakes.iter().find(|snake| snake.id == state.you.id).unwrap();
+
+    // Every move the search would go on to look past is already scored
+    let search = Search::<u128>::new(&board, you, &session.opponents, common::risk_limit()).unwrap();
+    let predictions = search.predict(&search.root);
+    let mut pondered = 0;
+    for move_dir in Move::ALL {
+        let next = search.advance(&search.root, &predictions, move_dir);
+        if next.us().is_some() && session.transpositions.get(next.key, 6).is_some() {
+            pondered += 1;
+        }
+    }
+    assert!(pondered > 0);
+
+    let scores = |table| logic::move_safety_scores(&board, you, &Move::ALL, 6, common::risk_limit(), &session.opponents, table, 1);
+    assert_eq!(scores(&session.transpositions), scores(&TranspositionTable::new(1 << 12)));
+}
+
+#[test]
+fn stopping_leaves_nothing_half_done() {
+    let state = common::four_snakes();
+    let session = session(&state, 9);
+    let pondering = ponder::start(&state, Move::Left, &session, common::risk_limit()).unwrap();
+    thread::sleep(Duration::from_millis(5));
+
+    let stopping = Instant::now();
+    pondering.stop();
+    assert!(stopping.elapsed() < Duration::from_secs(1));
+
+    // Whatever went in the table before it stopped was searched through
+    let board = simulation::advance(&state.board, &state.you, &Move::Left, &session.opponents);
+    let you = board.snakes.iter().find(|snake| snake.id == state.you.id).unwrap();
+    let scores = |table| logic::move_safety_scores(&board, you, &Move::ALL, 9, common::risk_limit(), &session.opponents, table, 1);
+    assert_eq!(scores(&session.transpositions), scores(&TranspositionTable::new(1 << 16)));
+}
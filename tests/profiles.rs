@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::fs;
+
+use battlesnake_rusty::opponent::OpponentModel;
+use battlesnake_rusty::profiles::{ProfileStore, StoredProfile};
+use battlesnake_rusty::{GameState, Move, ascii};
+
+// B heads up towards the food, passing up a move along the bottom wall
+fn before() -> GameState {
+    ascii::parse(
+        "
+        B: name greedy
+        # # # # # # #
+        # A a . . . #
+        # . . . . . #
+        # . . * . . #
+        # . . . . . #
+        # b b B . . #
+        # # # # # # #
+        ",
+    )
+    .unwrap()
+}
+
+fn after() -> GameState {
+    ascii::parse(
+        "
+        B: name greedy
+        # # # # # # #
+        # A a . . . #
+        # . . . . . #
+        # . . * . . #
+        # . . B . . #
+        # . b b . . #
+        # # # # # # #
+        ",
+    )
+    .unwrap()
+}
+
+#[test]
+fn records_games_and_reloads_them() {
+    let path = std::env::temp_dir().join(format!("opponent_profiles_test_{}.json", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let store = ProfileStore::load(&path);
+    let mut model = OpponentModel::default();
+    store.warm(&mut model, &after().board, &after().you);
+    for state in [before(), after()] {
+        model.observe(&state.game, &state.board, &state.you);
+    }
+
+    // Only A is left at the end, so it's a win against B
+    let mut end = after();
+    end.board.snakes.retain(|snake| snake.id == end.you.id);
+    store.record_game(&model, &end.board, &end.you);
+    // Dropping the store finishes writing it
+    drop(store);
+
+    let saved: HashMap<String, StoredProfile> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    let greedy = &saved["greedy"];
+    assert_eq!((greedy.games, greedy.wins, greedy.losses, greedy.draws), (1, 1, 0, 0));
+    assert_eq!(greedy.behaviour.food_greed.probability(), 2.0 / 3.0);
+
+    // A new game warms up with what the last one learned, even though B's id has changed
+    let store = ProfileStore::load(&path);
+    let mut next = after();
+    next.board.snakes[1].id = "another-game".to_string();
+    let mut warmed = OpponentModel::default();
+    store.warm(&mut warmed, &next.board, &next.you);
+    let b = &next.board.snakes[1];
+    assert_eq!(warmed.most_likely_move(b, &next.board), Some(Move::Up));
+    assert_ne!(warmed.move_probabilities(b, &next.board), OpponentModel::default().move_probabilities(b, &next.board));
+
+    drop(store);
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,281 @@
+use proptest::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+
+use battlesnake_rusty::rules::Settings;
+use battlesnake_rusty::session::GameSession;
+use battlesnake_rusty::simulation::{self, simulate_move};
+use battlesnake_rusty::strategy::{self, chosen_move};
+use battlesnake_rusty::transposition::{self, TranspositionTable};
+use battlesnake_rusty::weights::Weights;
+use battlesnake_rusty::{Battlesnake, Board, Coord, Game, GameState, Move, ascii, bitboard, logic};
+
+// Random but valid game states fed to every strategy, checking what must hold on any board
+
+
+#[derive(Debug, Clone)]
+struct Layout {
+    width: i32,
+    height: i32,
+    lengths: Vec<usize>,
+    food: usize,
+    hazards: usize,
+    seed: u64,
+}
+
+fn layouts() -> impl Strategy<Value = Layout> {
+    (1..=12i32, 1..=12i32, prop::collection::vec(1..=10usize, 1..=4), 0..=6usize, 0..=8usize, any::<u64>()).prop_map(
+        |(width, height, lengths, food, hazards, seed)| Layout {
+            width,
+            height,
+            lengths,
+            food,
+            hazards,
+            seed,
+        },
+    )
+}
+
+fn neighbours(coord: &Coord, width: i32, height: i32) -> Vec<Coord> {
+    Move::ALL
+        .iter()
+        .map(|move_dir| simulate_move(coord, move_dir))
+        .filter(|c| c.x >= 0 && c.y >= 0 && c.x < width && c.y < height)
+        .collect()
+}
+
+// Snakes are random walks over free squares, stacked on their tail when they run out of room.
+// Snakes that don't fit on the board at all are left off.
+fn build(layout: &Layout) -> GameState {
+    let mut rng = StdRng::seed_from_u64(layout.seed);
+    let mut taken: HashSet<Coord> = HashSet::new();
+    let mut snakes = Vec::new();
+
+    for (index, &length) in layout.lengths.iter().enumerate() {
+        let free: Vec<Coord> = (0..layout.width)
+            .flat_map(|x| (0..layout.height).map(move |y| Coord { x, y }))
+            .filter(|c| !taken.contains(c))
+            .collect();
+        let Some(head) = free.choose(&mut rng).cloned() else {
+            break;
+        };
+
+        let mut body = vec![head.clone()];
+        taken.insert(head.clone());
+        while body.len() < length {
+            let last = body.last().unwrap().clone();
+            let next = neighbours(&last, layout.width, layout.height)
+                .into_iter()
+                .filter(|c| !taken.contains(c))
+                .collect::<Vec<_>>()
+                .choose(&mut rng)
+                .cloned();
+            let next = next.unwrap_or(last);
+            taken.insert(next.clone());
+            body.push(next);
+        }
+
+        snakes.push(Battlesnake {
+            id: format!("snake-{}", index),
+            name: format!("snake-{}", index),
+            health: rng.random_range(1..=100),
+            head: head.clone(),
+            length: body.len() as i32,
+            body,
+            latency: "0".to_string(),
+            shout: None,
+        });
+    }
+
+    let mut free: Vec<Coord> = (0..layout.width)
+        .flat_map(|x| (0..layout.height).map(move |y| Coord { x, y }))
+        .filter(|c| !taken.contains(c))
+        .collect();
+    free.shuffle(&mut rng);
+    let food: Vec<Coord> = free.iter().take(layout.food).cloned().collect();
+    let hazards: Vec<Coord> = (0..layout.hazards)
+        .map(|_| Coord {
+            x: rng.random_range(0..layout.width),
+            y: rng.random_range(0..layout.height),
+        })
+        .collect();
+
+    GameState {
+        game: Game {
+            id: "property".to_string(),
+            ruleset: Settings::default().to_ruleset_json(),
+            timeout: 500,
+        },
+        turn: rng.random_range(0..300),
+        you: snakes[0].clone(),
+        board: Board {
+            height: layout.height,
+            width: layout.width,
+            food,
+            snakes,
+            hazards,
+        },
+    }
+}
+
+fn in_bounds(board: &Board, coord: &Coord) -> bool {
+    coord.x >= 0 && coord.y >= 0 && coord.x < board.width && coord.y < board.height
+}
+
+// Off the board, or into a square that's still taken after every snake moves
+fn certainly_fatal(board: &Board, you: &Battlesnake, move_dir: Move) -> bool {
+    let target = simulate_move(&you.head, &move_dir);
+    if !in_bounds(board, &target) {
+        return true;
+    }
+    board.snakes.iter().any(|snake| {
+        let tail_moves = snake.body.len() < 2 || snake.body[snake.body.len() - 1] != snake.body[snake.body.len() - 2];
+        let staying = if tail_moves { &snake.body[..snake.body.len() - 1] } else { &snake.body[..] };
+        staying.contains(&target)
+    })
+}
+
+// On the board and clear of every body square, tails included
+fn clearly_safe(board: &Board, you: &Battlesnake, move_dir: Move) -> bool {
+    let target = simulate_move(&you.head, &move_dir);
+    in_bounds(board, &target) && !board.snakes.iter().any(|snake| snake.body.contains(&target))
+}
+
+fn moves_for(state: &GameState) -> Vec<(String, Option<Move>)> {
+    let strategies = strategy::registered();
+    strategies
+        .names()
+        .map(|name| {
+            let mut session = GameSession::default();
+            session.opponents.observe(&state.game, &state.board, &state.you);
+            let response = strategies.get(name).unwrap().get_move(state, &mut session);
+            (name.to_string(), chosen_move(&response))
+        })
+        .collect()
+}
+
+// Rusty's look-ahead is slow in debug builds, so run fewer cases than proptest would unless asked
+fn cases() -> u32 {
+    std::env::var("PROPTEST_CASES").ok().and_then(|cases| cases.parse().ok()).unwrap_or(64)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(cases()))]
+
+    #[test]
+    fn always_answers_with_a_move(layout in layouts()) {
+        let state = build(&layout);
+        for (name, chosen) in moves_for(&state) {
+            prop_assert!(chosen.is_some(), "{} gave no move on\n{}", name, ascii::render(&state));
+        }
+    }
+
+    #[test]
+    fn never_dies_needlessly(layout in layouts()) {
+        let state = build(&layout);
+        if !Move::ALL.iter().any(|m| clearly_safe(&state.board, &state.you, *m)) {
+            return Ok(());
+        }
+
+        for (name, chosen) in moves_for(&state) {
+            let chosen = chosen.unwrap();
+            prop_assert!(
+                !certainly_fatal(&state.board, &state.you, chosen),
+                "{} chose {} with a safe move available on\n{}",
+                name,
+                chosen.as_str(),
+                ascii::render(&state)
+            );
+        }
+    }
+
+    #[test]
+    fn survives_malformed_snakes(layout in layouts(), empty_body in any::<bool>(), outside in any::<bool>()) {
+        let mut state = build(&layout);
+        if empty_body {
+            state.you.body.clear();
+            state.board.snakes[0].body.clear();
+        }
+        if outside {
+            state.you.head = Coord { x: -1, y: state.board.height };
+        }
+        for (name, chosen) in moves_for(&state) {
+            prop_assert!(chosen.is_some(), "{} gave no move", name);
+        }
+    }
+
+    #[test]
+    fn bitboard_search_matches_board_search(layout in layouts()) {
+        let state = build(&layout);
+        let mut session = GameSession::default();
+        session.opponents.observe(&state.game, &state.board, &state.you);
+
+        // Separate tables, each shared by all four moves as in a game
+        let risk_limit = Weights::default().head_to_head_risk_limit;
+        let (board_table, bitboard_table) = (TranspositionTable::new(1024), TranspositionTable::new(1024));
+        for move_dir in Move::ALL {
+            let opponents = &session.opponents;
+            let expected = logic::evaluate_move_safety(&state.board, &state.you, &move_dir, 4, risk_limit, opponents, &board_table);
+            let score = bitboard::move_safety(&state.board, &state.you, move_dir, 4, risk_limit, opponents, &bitboard_table);
+            prop_assert_eq!(score, Some(expected), "{} on\n{}", move_dir.as_str(), ascii::render(&state));
+        }
+    }
+
+    #[test]
+    fn bitboard_keys_match_board_keys(layout in layouts()) {
+        let state = build(&layout);
+        let mut session = GameSession::default();
+        session.opponents.observe(&state.game, &state.board, &state.you);
+        let risk_limit = Weights::default().head_to_head_risk_limit;
+        let search = bitboard::Search::<bitboard::WideBits>::new(&state.board, &state.you, &session.opponents, risk_limit).unwrap();
+        prop_assert_eq!(search.root.key, transposition::board_key(&state.board));
+
+        let predictions = search.predict(&search.root);
+        for move_dir in Move::ALL {
+            // Off the board the bitboard takes us off too, where `Board` keeps us for the caller to rule out
+            let head = simulate_move(&state.you.head, &move_dir);
+            if head.x < 0 || head.x >= state.board.width || head.y < 0 || head.y >= state.board.height {
+                continue;
+            }
+            let position = search.advance(&search.root, &predictions, move_dir);
+            let board = simulation::advance(&state.board, &state.you, &move_dir, &session.opponents);
+            prop_assert_eq!(position.key, transposition::board_key(&board), "{} on\n{}", move_dir.as_str(), ascii::render(&state));
+        }
+    }
+}
+
+// The edge cases named outright, rather than left to the generator's luck
+
+#[test]
+fn one_square_board() {
+    let state = build(&Layout {
+        width: 1,
+        height: 1,
+        lengths: vec![3],
+        food: 0,
+        hazards: 1,
+        seed: 0,
+    });
+    for (name, chosen) in moves_for(&state) {
+        assert!(chosen.is_some(), "{} gave no move", name);
+    }
+}
+
+#[test]
+fn empty_body() {
+    let mut state = build(&Layout {
+        width: 11,
+        height: 11,
+        lengths: vec![3, 3],
+        food: 2,
+        hazards: 0,
+        seed: 0,
+    });
+    state.you.body.clear();
+    state.board.snakes[0].body.clear();
+    for (name, chosen) in moves_for(&state) {
+        assert!(chosen.is_some(), "{} gave no move", name);
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use battlesnake_rusty::session::GameSession;
+use battlesnake_rusty::strategy::chosen_move;
+use battlesnake_rusty::weights::Weights;
+use battlesnake_rusty::{GameState, Move, ascii, logic};
+
+// Tactical puzzles from `puzzles/`: a board in the ASCII format, plus lines saying which moves are
+// acceptable and which are not.
+//
+//   note: what the puzzle is about
+//   expect: up, left     the move must be one of these
+//   forbid: down         the move must not be any of these
+//   pending: why         a known failure, reported but not failed on
+//
+// Rusty breaks some ties at random, so every puzzle is played several times.
+const ATTEMPTS: usize = 5;
+
+struct Puzzle {
+    name: String,
+    note: String,
+    state: GameState,
+    expect: Vec<Move>,
+    forbid: Vec<Move>,
+    pending: Option<String>,
+}
+
+fn moves(value: &str) -> Result<Vec<Move>, String> {
+    value.split(',').map(str::trim).filter(|m| !m.is_empty()).map(str::parse).collect()
+}
+
+fn load(path: &Path) -> Result<Puzzle, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let (mut note, mut expect, mut forbid, mut pending) = (String::new(), Vec::new(), Vec::new(), None);
+    let mut board = String::new();
+
+    for line in text.lines() {
+        match line.trim().split_once(':') {
+            Some(("note", value)) => note = value.trim().to_string(),
+            Some(("expect", value)) => expect = moves(value)?,
+            Some(("forbid", value)) => forbid = moves(value)?,
+            Some(("pending", value)) => pending = Some(value.trim().to_string()),
+            _ => {
+                board.push_str(line);
+                board.push('\n');
+            }
+        }
+    }
+
+    if expect.is_empty() && forbid.is_empty() {
+        return Err("needs an expect or forbid line".to_string());
+    }
+    Ok(Puzzle {
+        name: path.file_stem().unwrap().to_string_lossy().to_string(),
+        note,
+        state: ascii::parse(&board)?,
+        expect,
+        forbid,
+        pending,
+    })
+}
+
+// Why the move fails the puzzle, if it does
+fn check(puzzle: &Puzzle, chosen: Option<Move>) -> Option<String> {
+    let Some(chosen) = chosen else {
+        return Some("no move".to_string());
+    };
+    let names = |moves: &[Move]| moves.iter().map(Move::as_str).collect::<Vec<_>>().join(", ");
+
+    if !puzzle.expect.is_empty() && !puzzle.expect.contains(&chosen) {
+        return Some(format!("chose {}, expected {}", chosen.as_str(), names(&puzzle.expect)));
+    }
+    if puzzle.forbid.contains(&chosen) {
+        return Some(format!("chose {}, which is forbidden", chosen.as_str()));
+    }
+    None
+}
+
+fn solve(puzzle: &Puzzle) -> Option<Move> {
+    let state = &puzzle.state;
+    let mut session = GameSession::default();
+    session.opponents.observe(&state.game, &state.board, &state.you);
+    let weights = Weights::default();
+    chosen_move(&logic::get_move(&state.game, &state.turn, &state.board, &state.you, &mut session, &weights))
+}
+
+#[test]
+fn puzzles() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("puzzles");
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no puzzles in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for path in &paths {
+        let puzzle = match load(path) {
+            Ok(puzzle) => puzzle,
+            Err(e) => {
+                failures.push(format!("{}: {}", path.display(), e));
+                continue;
+            }
+        };
+
+        let failure = (0..ATTEMPTS).find_map(|_| check(&puzzle, solve(&puzzle)));
+        match (&failure, &puzzle.pending) {
+            (None, _) => println!("pass     {} ({})", puzzle.name, puzzle.note),
+            (Some(why), Some(pending)) => println!("pending  {}: {} ({})", puzzle.name, why, pending),
+            (Some(why), None) => {
+                println!("FAIL     {}: {} ({})", puzzle.name, why, puzzle.note);
+                failures.push(format!("{}: {}\n{}", puzzle.name, why, ascii::render(&puzzle.state)));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{} of {} puzzles failed:\n\n{}", failures.len(), paths.len(), failures.join("\n"));
+}
@@ -0,0 +1,171 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::{Value, json};
+
+use battlesnake_rusty::arena::{self, ArenaConfig, Contestant};
+use battlesnake_rusty::recorder::{self, Event, Record, Recorder};
+use battlesnake_rusty::replay::{self, Verdict};
+use battlesnake_rusty::rules::{Ruleset, Settings};
+use battlesnake_rusty::session::GameSession;
+use battlesnake_rusty::strategy::{Rusty, Strategies, Strategy};
+use battlesnake_rusty::weights::Profile;
+use battlesnake_rusty::{GameState, Move};
+
+// Heads left until it hits the wall
+struct Lefty;
+
+impl Strategy for Lefty {
+    fn info(&self) -> Value {
+        json!({ "apiversion": "1" })
+    }
+
+    fn get_move(&self, _state: &GameState, _session: &mut GameSession) -> Value {
+        json!({ "move": "left" })
+    }
+}
+
+// Records every request and answer the way the server does
+struct Recording<'a> {
+    name: &'a str,
+    strategy: &'a dyn Strategy,
+    recorder: &'a Recorder,
+}
+
+impl Strategy for Recording<'_> {
+    fn info(&self) -> Value {
+        self.strategy.info()
+    }
+
+    fn start(&self, state: &GameState, session: &mut GameSession) {
+        self.strategy.start(state, session);
+        self.recorder.record(Record::new(Event::Start, self.name, state));
+    }
+
+    fn get_move(&self, state: &GameState, session: &mut GameSession) -> Value {
+        let response = self.strategy.get_move(state, session);
+        self.recorder.record(Record::new(Event::Move, self.name, state).with_response(&response));
+        response
+    }
+
+    fn end(&self, state: &GameState, session: &mut GameSession) {
+        self.strategy.end(state, session);
+        self.recorder.record(Record::new(Event::End, self.name, state));
+    }
+}
+
+fn strategies() -> Strategies {
+    Strategies::default().register("lefty", Lefty).register("rusty", Rusty::new(Profile::default()))
+}
+
+// A short solo game on a small board played by `name`, recorded into `dir`
+fn record_game(dir: &PathBuf, strategies: &Strategies, name: &str) -> Vec<Record> {
+    let _ = fs::remove_dir_all(dir);
+    let recorder = Recorder::new(Some(dir.clone()));
+    let config = ArenaConfig {
+        width: 5,
+        height: 5,
+        settings: Settings {
+            ruleset: Ruleset::Solo,
+            ..Settings::default()
+        },
+        max_turns: Some(8),
+        seed: 3,
+        ..ArenaConfig::default()
+    };
+    let recording = Recording {
+        name,
+        strategy: strategies.get(name).unwrap(),
+        recorder: &recorder,
+    };
+    arena::play_game(
+        &config,
+        &[Contestant {
+            name: name.to_string(),
+            strategy: &recording,
+        }],
+    )
+    .unwrap();
+    // Dropping the recorder waits for everything it was given to be written
+    drop(recorder);
+
+    // One file per game, named after its id, with a line per request
+    let paths = recorder::recordings(&[dir.display().to_string()]).unwrap();
+    assert_eq!(paths.len(), 1, "{:?}", paths);
+    let records = recorder::load(&paths[0]).unwrap();
+    let file_name = paths[0].file_name().unwrap().to_str().unwrap();
+    assert_eq!(file_name, format!("{}.jsonl", records[0].state.game.id));
+    assert_eq!(fs::read_to_string(&paths[0]).unwrap().lines().count(), records.len());
+    records
+}
+
+#[test]
+fn recorded_games_append_a_line_per_request() {
+    let dir = std::env::temp_dir().join(format!("recordings_test_{}", std::process::id()));
+    let records = record_game(&dir, &strategies(), "lefty");
+
+    let events: Vec<Event> = records.iter().map(|record| record.event).collect();
+    assert_eq!(events.first(), Some(&Event::Start));
+    assert_eq!(events.last(), Some(&Event::End));
+    assert!(events[1..events.len() - 1].iter().all(|event| *event == Event::Move));
+    assert!(records.iter().all(|record| record.snake == "lefty"));
+    let moves = &records[1..records.len() - 1];
+    assert!(moves.iter().all(|record| record.move_dir == Some(Move::Left)));
+    assert!(moves.windows(2).all(|pair| pair[1].state.turn == pair[0].state.turn + 1));
+
+    // Game ids can't escape the directory
+    let recorder = Recorder::new(Some(dir.clone()));
+    let mut escaping = records[0].clone();
+    escaping.state.game.id = "../escaped".to_string();
+    recorder.record(escaping.clone());
+    recorder.record(escaping);
+    drop(recorder);
+    let escaped = recorder::load(&dir.join("___escaped.jsonl")).unwrap();
+    assert_eq!(escaped.len(), 2);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn replaying_a_fatal_move_with_a_safer_strategy_avoids_the_death() {
+    let dir = std::env::temp_dir().join(format!("replay_test_{}", std::process::id()));
+    let strategies = strategies();
+    let records = record_game(&dir, &strategies, "lefty");
+    let moves: Vec<&Record> = records.iter().filter(|record| record.event == Event::Move).collect();
+    let fatal = moves.last().unwrap();
+    // It walks into the wall from the left column
+    assert_eq!(fatal.state.you.head.x, 0);
+
+    // The same strategy makes the same moves
+    let same = replay::replay(&records, &strategies, None, 5).unwrap();
+    assert_eq!(same.len(), 1);
+    assert_eq!(same[0].strategy, "lefty");
+    assert_eq!(same[0].moves, moves.len());
+    assert_eq!(same[0].death_turn, Some(fatal.state.turn + 1));
+    assert!(same[0].differences.is_empty());
+
+    let safer = &replay::replay(&records, &strategies, Some("rusty"), 5).unwrap()[0];
+    let last = safer.differences.last().unwrap();
+    assert_eq!((last.turn, last.recorded), (fatal.state.turn, Move::Left));
+    assert_ne!(last.replayed, Move::Left);
+    assert_eq!(last.verdict, Verdict::AvoidsDeath);
+    assert!(safer.differences.iter().all(|difference| difference.verdict == Verdict::AvoidsDeath));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn replaying_a_surviving_game_with_a_fatal_strategy_is_a_regression() {
+    let dir = std::env::temp_dir().join(format!("regression_test_{}", std::process::id()));
+    let strategies = strategies();
+    let records = record_game(&dir, &strategies, "rusty");
+
+    let survived = &replay::replay(&records, &strategies, None, 5).unwrap()[0];
+    assert_eq!(survived.death_turn, None);
+
+    let lefty = &replay::replay(&records, &strategies, Some("lefty"), 5).unwrap()[0];
+    assert!(!lefty.differences.is_empty());
+    assert!(lefty.differences.iter().any(|difference| difference.verdict == Verdict::Regression), "{:?}", lefty.differences);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use battlesnake_rusty::rules::{self, EliminationCause, Ruleset, Settings};
+use battlesnake_rusty::{Battlesnake, Board, Coord, Move, ascii};
+
+fn board(text: &str) -> Board {
+    ascii::parse(text).unwrap().board
+}
+
+// No food turns up unless a test asks for it
+fn settings(ruleset: Ruleset) -> Settings {
+    Settings {
+        ruleset,
+        food_spawn_chance: 0,
+        minimum_food: 0,
+        ..Settings::default()
+    }
+}
+
+// Step `board` with each snake's move, returning each elimination's snake, cause and culprit
+fn step(board: &mut Board, moves: &[(&str, Move)], settings: &Settings) -> Vec<(String, EliminationCause, Option<String>)> {
+    let moves: HashMap<String, Move> = moves.iter().map(|(id, move_dir)| (id.to_string(), *move_dir)).collect();
+    rules::step(board, 1, &moves, settings, &mut StdRng::seed_from_u64(0))
+        .into_iter()
+        .map(|elimination| (elimination.snake_id, elimination.cause, elimination.by))
+        .collect()
+}
+
+fn snake<'a>(board: &'a Board, id: &str) -> &'a Battlesnake {
+    board.snakes.iter().find(|snake| snake.id == id).unwrap()
+}
+
+#[test]
+fn eating_restores_health_and_grows_the_tail() {
+    let mut board = board(
+        "
+        A: health 40
+        # # # # #
+        # . * . #
+        # . A . #
+        # . a . #
+        # # # # #
+        ",
+    );
+
+    assert!(step(&mut board, &[("A", Move::Up)], &settings(Ruleset::Standard)).is_empty());
+    let a = snake(&board, "A");
+    assert_eq!(a.health, 100);
+    assert_eq!(a.length, 3);
+    assert_eq!(a.body, [Coord { x: 1, y: 2 }, Coord { x: 1, y: 1 }, Coord { x: 1, y: 1 }]);
+    assert!(board.food.is_empty());
+}
+
+#[test]
+fn stacked_hazards_each_do_damage() {
+    let mut board = board(
+        "
+        A: health 90
+        # # # # #
+        # . ~ . #
+        # . A . #
+        # . a . #
+        # # # # #
+        ",
+    );
+    board.hazards.push(Coord { x: 1, y: 2 });
+
+    step(&mut board, &[("A", Move::Up)], &settings(Ruleset::Standard));
+    assert_eq!(snake(&board, "A").health, 90 - 1 - 2 * 14);
+}
+
+#[test]
+fn food_in_a_hazard_does_no_damage() {
+    let mut board = board(
+        "
+        A: health 90
+        # # # # #
+        # . % . #
+        # . A . #
+        # . a . #
+        # # # # #
+        ",
+    );
+
+    step(&mut board, &[("A", Move::Up)], &settings(Ruleset::Standard));
+    assert_eq!(snake(&board, "A").health, 100);
+}
+
+#[test]
+fn snakes_starve_without_food() {
+    let mut board = board(
+        "
+        A: health 1
+        B: health 2
+        # # # # #
+        # . . . #
+        # A . B #
+        # a . b #
+        # # # # #
+        ",
+    );
+
+    let eliminations = step(&mut board, &[("A", Move::Up), ("B", Move::Up)], &settings(Ruleset::Standard));
+    assert_eq!(eliminations, [("A".to_string(), EliminationCause::OutOfHealth, None)]);
+    assert_eq!(snake(&board, "B").health, 1);
+}
+
+#[test]
+fn running_into_itself_is_a_self_collision() {
+    let mut board = board(
+        "
+        # # # # #
+        # a a a #
+        # A . a #
+        # . . . #
+        # # # # #
+        ",
+    );
+
+    let eliminations = step(&mut board, &[("A", Move::Up)], &settings(Ruleset::Standard));
+    assert_eq!(eliminations, [("A".to_string(), EliminationCause::SelfCollision, None)]);
+    assert!(board.snakes.is_empty());
+}
+
+#[test]
+fn running_into_another_body_is_a_snake_collision() {
+    let mut board = board(
+        "
+        # # # # # #
+        # . . b b #
+        # a A b . #
+        # . . B . #
+        # # # # # #
+        ",
+    );
+
+    let eliminations = step(&mut board, &[("A", Move::Right), ("B", Move::Right)], &settings(Ruleset::Standard));
+    assert_eq!(eliminations, [("A".to_string(), EliminationCause::SnakeCollision, Some("B".to_string()))]);
+    assert_eq!(board.snakes.len(), 1);
+}
+
+#[test]
+fn the_shorter_snake_loses_a_head_to_head() {
+    let mut board = board(
+        "
+        # # # # # # # #
+        # a A . B b b #
+        # # # # # # # #
+        ",
+    );
+
+    let eliminations = step(&mut board, &[("A", Move::Right), ("B", Move::Left)], &settings(Ruleset::Standard));
+    assert_eq!(eliminations, [("A".to_string(), EliminationCause::HeadCollision, Some("B".to_string()))]);
+    assert_eq!(board.snakes.len(), 1);
+}
+
+#[test]
+fn snakes_of_the_same_length_both_lose_a_head_to_head() {
+    let mut board = board(
+        "
+        # # # # # # #
+        # a A . B b #
+        # # # # # # #
+        ",
+    );
+
+    let eliminations = step(&mut board, &[("A", Move::Right), ("B", Move::Left)], &settings(Ruleset::Standard));
+    assert_eq!(
+        eliminations,
+        [
+            ("A".to_string(), EliminationCause::HeadCollision, Some("B".to_string())),
+            ("B".to_string(), EliminationCause::HeadCollision, Some("A".to_string())),
+        ]
+    );
+    assert!(board.snakes.is_empty());
+}
+
+#[test]
+fn constrictor_snakes_grow_every_turn_at_full_health() {
+    let mut board = board(
+        "
+        A: health 50
+        # # # # #
+        # . . . #
+        # . A . #
+        # . a . #
+        # # # # #
+        ",
+    );
+
+    step(&mut board, &[("A", Move::Up)], &settings(Ruleset::Constrictor));
+    let a = snake(&board, "A");
+    assert_eq!((a.health, a.length), (100, 3));
+    step(&mut board, &[("A", Move::Left)], &settings(Ruleset::Constrictor));
+    let a = snake(&board, "A");
+    assert_eq!((a.health, a.length), (100, 4));
+    assert_eq!(a.body[2], a.body[3]);
+}
+
+#[test]
+fn food_spawns_before_eliminations() {
+    // A starves on the way down, and the square it still lies on stays taken until food has spawned
+    for seed in 0..20 {
+        let mut board = board(
+            "
+            A: health 1
+            # # # # # # #
+            # A a . B b #
+            # # # # # # #
+            ",
+        );
+        let moves = HashMap::from([("A".to_string(), Move::Down), ("B".to_string(), Move::Left)]);
+        let settings = Settings {
+            minimum_food: 1,
+            ..settings(Ruleset::Standard)
+        };
+        rules::step(&mut board, 1, &moves, &settings, &mut StdRng::seed_from_u64(seed));
+
+        assert_eq!(board.food.len(), 1);
+        assert_ne!(board.food[0], Coord { x: 0, y: 0 }, "seed {}", seed);
+    }
+}
+
+#[test]
+fn fixed_starts_hand_out_corners_or_cardinals_first() {
+    let snakes = [("A".to_string(), "A".to_string())];
+    let mut corners = 0;
+    for seed in 0..40 {
+        let board = rules::create_board(11, 11, &snakes, &settings(Ruleset::Standard), &mut StdRng::seed_from_u64(seed)).unwrap();
+        let head = &board.snakes[0].head;
+        if [1, 9].contains(&head.x) && [1, 9].contains(&head.y) {
+            corners += 1;
+        } else {
+            assert!(head.x == 5 || head.y == 5, "{:?}", head);
+        }
+    }
+    assert!(corners > 0 && corners < 40, "{} corner starts", corners);
+}
@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use battlesnake_rusty::ascii;
+use battlesnake_rusty::logic::LOOK_AHEAD_DEPTH;
+use battlesnake_rusty::scheduler::{self, Load, MIN_DEPTH, Timing};
+use battlesnake_rusty::session::GameSession;
+use battlesnake_rusty::weights::Weights;
+
+const BUDGET: Duration = Duration::from_millis(250);
+
+fn timing(depth: i32, elapsed_ms: u64, share: f64) -> Timing {
+    Timing {
+        depth,
+        elapsed: Duration::from_millis(elapsed_ms),
+        share,
+    }
+}
+
+#[test]
+fn searches_fully_until_it_knows_better() {
+    let load = Load { games: 50, threads: 1 };
+    assert_eq!(scheduler::depth(None, load, BUDGET, &Weights::default()), LOOK_AHEAD_DEPTH);
+}
+
+#[test]
+fn keeps_the_full_depth_while_it_fits() {
+    let last = timing(LOOK_AHEAD_DEPTH, 20, 2.0);
+    let load = Load { games: 4, threads: 8 };
+    assert_eq!(scheduler::depth(Some(last), load, BUDGET, &Weights::default()), LOOK_AHEAD_DEPTH);
+}
+
+#[test]
+fn gives_up_depth_as_games_pile_up() {
+    // 20ms on two threads' worth of CPU, and each move deeper costs three times as much
+    let last = timing(LOOK_AHEAD_DEPTH, 20, 2.0);
+    let depth = |games| scheduler::depth(Some(last), Load { games, threads: 8 }, BUDGET, &Weights::default());
+
+    // A sixth of a thread each makes it 240ms, which still fits
+    assert_eq!(depth(48), LOOK_AHEAD_DEPTH);
+    // A tenth of one makes it 400ms, and one move less 133ms
+    assert_eq!(depth(80), LOOK_AHEAD_DEPTH - 1);
+    // A fortieth of one makes it 1600ms, and two moves less 178ms
+    assert_eq!(depth(320), LOOK_AHEAD_DEPTH - 2);
+    // Hopelessly overloaded, it still looks a little ahead
+    assert_eq!(depth(1_000_000), MIN_DEPTH);
+}
+
+#[test]
+fn recovers_its_depth_when_the_load_drops() {
+    let last = timing(LOOK_AHEAD_DEPTH - 2, 100, 0.1);
+    let load = Load { games: 2, threads: 8 };
+    assert_eq!(scheduler::depth(Some(last), load, BUDGET, &Weights::default()), LOOK_AHEAD_DEPTH);
+}
+
+#[test]
+fn plans_each_turn_from_the_last() {
+    let state = ascii::parse(
+        "
+        # # # # #
+        # A a . #
+        # . . . #
+        # # # # #
+        ",
+    )
+    .unwrap();
+    let mut session = GameSession::default();
+    let load = Load { games: 3, threads: 8 };
+
+    scheduler::plan(&mut session, &state.game, load, &Weights::default());
+    assert_eq!(session.threads, Some(2));
+    assert_eq!(session.depth, Some(LOOK_AHEAD_DEPTH));
+
+    // Far too slow for the game's timeout
+    scheduler::record(&mut session, load, Duration::from_secs(10));
+    assert_eq!(session.timing, Some(timing(LOOK_AHEAD_DEPTH, 10_000, load.share())));
+    scheduler::plan(&mut session, &state.game, load, &Weights::default());
+    assert!(session.depth < Some(LOOK_AHEAD_DEPTH));
+}
@@ -0,0 +1,76 @@
+use battlesnake_rusty::arena::ArenaConfig;
+use battlesnake_rusty::spsa::{self, Spsa, SpsaConfig};
+use battlesnake_rusty::tournament::{Score, TournamentConfig};
+use battlesnake_rusty::weights::{Profile, Weights};
+
+// A stand-in for self-play, where the weights closer to the ideal win the whole batch
+fn closer_wins(ideal: &Weights) -> impl Fn(&Weights, &Weights) -> Result<Score, String> {
+    let distance = |w: &Weights| {
+        (w.low_health - ideal.low_health).abs() * 10 / 8
+            + (w.look_ahead_depth - ideal.look_ahead_depth).abs() * 10
+            + (w.wall_buffer - ideal.wall_buffer).abs() * 10
+    };
+    move |plus, minus| {
+        let (plus, minus) = (distance(plus), distance(minus));
+        Ok(Score {
+            wins: (plus < minus) as u32 * 10,
+            draws: (plus == minus) as u32 * 10,
+            losses: (plus > minus) as u32 * 10,
+        })
+    }
+}
+
+#[test]
+fn moves_towards_the_weights_that_win() {
+    let ideal = Weights {
+        low_health: 60,
+        look_ahead_depth: 5,
+        wall_buffer: 1,
+        ..Weights::default()
+    };
+    let start = Weights::default();
+    let mut spsa = Spsa::new(
+        &start,
+        SpsaConfig {
+            iterations: 200,
+            learning_rate: 1.0,
+            seed: 7,
+        },
+    );
+
+    let play = closer_wins(&ideal);
+    while !spsa.done() {
+        let iteration = spsa.iterate(&play).unwrap();
+        assert_ne!(iteration.plus, iteration.minus);
+    }
+
+    let tuned = spsa.weights();
+    assert!((tuned.low_health - ideal.low_health).abs() <= 8, "{:?}", tuned);
+    assert!((tuned.look_ahead_depth - ideal.look_ahead_depth).abs() <= 1, "{:?}", tuned);
+    assert!((tuned.wall_buffer - ideal.wall_buffer).abs() <= 1, "{:?}", tuned);
+}
+
+#[test]
+fn plays_rusty_against_itself() {
+    let config = TournamentConfig {
+        arena: ArenaConfig {
+            width: 7,
+            height: 7,
+            max_turns: Some(20),
+            seed: 3,
+            ..ArenaConfig::default()
+        },
+        games: 2,
+        threads: 2,
+        sprt: None,
+    };
+    let shallow = Weights {
+        look_ahead_depth: 2,
+        ..Weights::default()
+    };
+
+    let score = spsa::self_play(&config, &Profile::default(), &shallow, &shallow).unwrap();
+    assert_eq!(score.games(), 2);
+    let tuned = spsa::profile_with(&Profile::default(), &config, &shallow);
+    assert_eq!(tuned.for_ruleset("standard"), &shallow);
+}
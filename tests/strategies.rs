@@ -0,0 +1,85 @@
+use battlesnake_rusty::session::GameSession;
+use battlesnake_rusty::strategy::{Cautious, Evaluated, Strategy, chosen_move};
+use battlesnake_rusty::weights::Profile;
+use battlesnake_rusty::{GameState, Move, ascii};
+use serde_json::json;
+
+// Alone in the middle of an empty board, every move is as good as any other
+fn symmetric() -> GameState {
+    ascii::parse(
+        "
+        # # # # # # #
+        # . . . . . #
+        # . . . . . #
+        # . . A . . #
+        # . . . . . #
+        # . . . . . #
+        # # # # # # #
+        ",
+    )
+    .unwrap()
+}
+
+fn moves(strategy: &dyn Strategy) -> Vec<Option<Move>> {
+    let state = symmetric();
+    (0..20)
+        .map(|_| {
+            let mut session = GameSession {
+                depth: Some(3),
+                ..GameSession::default()
+            };
+            chosen_move(&strategy.get_move(&state, &mut session))
+        })
+        .collect()
+}
+
+#[test]
+fn cautious_breaks_ties_in_move_order() {
+    assert!(moves(&Cautious::default()).iter().all(|chosen| *chosen == Some(Move::Up)));
+}
+
+#[test]
+fn evaluated_breaks_ties_in_move_order() {
+    let strategy = Evaluated::new(Profile::default(), json!({}));
+    assert!(moves(&strategy).iter().all(|chosen| *chosen == Some(Move::Up)));
+}
+
+#[test]
+fn strategies_look_ahead_as_far_as_the_profile_says() {
+    let profile = Profile::parse("[default]\nlook_ahead_depth = 3\n").unwrap();
+    let game = symmetric().game;
+    assert_eq!(Cautious::new(profile.clone()).weights(&game).look_ahead_depth, 3);
+    assert_eq!(Evaluated::new(profile, json!({})).weights(&game).look_ahead_depth, 3);
+}
+
+#[test]
+fn evaluated_plays_by_the_profiles_evaluation() {
+    let state = ascii::parse(
+        "
+        # # # # # # # # #
+        # . . . . . . . #
+        # . . . . . . . #
+        # * . . A . . . #
+        # . . . . . . . #
+        # . . . . . . . #
+        # # # # # # # # #
+        ",
+    )
+    .unwrap();
+    // Only the distance to food counts, for better or worse
+    let choose = |food_distance: f64| {
+        let profile = Profile::parse(&format!(
+            "[default.evaluation]\nlength = 0.0\nhealth = 0.0\nspace = 0.0\nopponents = 0.0\ncentre = 0.0\nfood_distance = {:?}\n",
+            food_distance
+        ))
+        .unwrap();
+        let mut session = GameSession {
+            depth: Some(3),
+            ..GameSession::default()
+        };
+        chosen_move(&Evaluated::new(profile, json!({})).get_move(&state, &mut session))
+    };
+
+    assert_eq!(choose(-5.0), Some(Move::Left));
+    assert_ne!(choose(5.0), Some(Move::Left));
+}
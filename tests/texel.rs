@@ -0,0 +1,114 @@
+use battlesnake_rusty::evaluation::{self, Evaluation, Evaluator};
+use battlesnake_rusty::recorder::{Event, Record};
+use battlesnake_rusty::texel::{self, Sample, TexelConfig};
+use battlesnake_rusty::{GameState, ascii};
+
+fn board(text: &str) -> GameState {
+    ascii::parse(text).unwrap()
+}
+
+#[test]
+fn labels_every_move_with_how_the_game_ended() {
+    let playing = board(
+        "
+        # # # # # # #
+        # . . . . . #
+        # A a . . . #
+        # . . . B b #
+        # . . . . . #
+        # # # # # # #
+        ",
+    );
+    let mut won = playing.clone();
+    won.board.snakes.retain(|s| s.id == won.you.id);
+    let mut lost = playing.clone();
+    lost.board.snakes.retain(|s| s.id != lost.you.id);
+
+    let game = |end: &GameState| {
+        vec![
+            Record::new(Event::Start, "rusty", &playing),
+            Record::new(Event::Move, "rusty", &playing),
+            Record::new(Event::Move, "rusty", &playing),
+            Record::new(Event::End, "rusty", end),
+        ]
+    };
+    let results = |end: &GameState| texel::samples(&game(end)).iter().map(|s| s.result).collect::<Vec<_>>();
+
+    assert_eq!(results(&won), vec![1.0, 1.0]);
+    assert_eq!(results(&lost), vec![0.0, 0.0]);
+    assert_eq!(results(&playing), vec![0.5, 0.5]);
+    // Without an ending there's nothing to learn from
+    assert!(texel::samples(&game(&won)[..3]).is_empty());
+}
+
+#[test]
+fn space_counts_what_the_head_can_reach() {
+    let open = board(
+        "
+        # # # # # #
+        # . . . . #
+        # . A a . #
+        # . . a . #
+        # . . . . #
+        # # # # # #
+        ",
+    );
+    let boxed_in = board(
+        "
+        # # # # # #
+        # A b . . #
+        # a b . . #
+        # a B . . #
+        # . . . . #
+        # # # # # #
+        ",
+    );
+    let space = |state: &GameState| evaluation::features(&state.board, &state.you)[2];
+    assert_eq!(space(&open), 13.0 / 16.0);
+    assert_eq!(space(&boxed_in), 0.0);
+
+    let evaluation = Evaluation::default();
+    let chance = |state: &GameState| evaluation.evaluate(&state.board, &state.you);
+    assert!(chance(&open) > chance(&boxed_in));
+}
+
+#[test]
+fn opponents_count_as_a_share_of_the_snakes_left() {
+    let four = board(
+        "
+        # # # # # # #
+        # A a . . . #
+        # . . . B b #
+        # C c . . . #
+        # . . . D d #
+        # # # # # # #
+        ",
+    );
+    let opponents = |state: &GameState| evaluation::features(&state.board, &state.you)[4];
+    assert_eq!(opponents(&four), 0.75);
+
+    let mut alone = four.clone();
+    alone.board.snakes.retain(|snake| snake.id == alone.you.id);
+    assert_eq!(opponents(&alone), 0.0);
+}
+
+#[test]
+fn fits_the_evaluation_to_the_results() {
+    // Games were won by whoever had the most space, whatever their health
+    let samples: Vec<Sample> = (0..40)
+        .map(|i| {
+            let space = (i % 10) as f64 / 10.0;
+            let health = ((i * 7) % 10) as f64 / 10.0;
+            Sample {
+                features: [0.0, health, space, 0.5, 0.5, 0.5],
+                result: if space >= 0.5 { 1.0 } else { 0.0 },
+            }
+        })
+        .collect();
+
+    let start = Evaluation::default();
+    let tuned = texel::tune(&start, &samples, &TexelConfig::default());
+    assert!(texel::error(&tuned, &samples) < texel::error(&start, &samples) / 2.0);
+    assert!(tuned.space > start.space);
+    assert!(tuned.health.abs() < tuned.space.abs());
+}
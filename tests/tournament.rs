@@ -0,0 +1,72 @@
+use battlesnake_rusty::tournament::{Score, Sprt, SprtOutcome};
+
+fn sprt() -> Sprt {
+    Sprt {
+        elo0: 0.0,
+        elo1: 10.0,
+        alpha: 0.05,
+        beta: 0.05,
+    }
+}
+
+#[test]
+fn a_75_percent_score_is_plus_190_elo() {
+    let score = Score {
+        wins: 25,
+        draws: 10,
+        losses: 5,
+    };
+    let elo = score.elo().unwrap();
+    assert!((elo.difference - 190.85).abs() < 0.01, "{:?}", elo);
+    assert!(elo.lower < elo.difference && elo.difference < elo.upper);
+    assert!(score.to_string().starts_with("+190.8 Elo"), "{}", score);
+
+    assert!(Score::default().elo().is_none());
+    assert_eq!(Score::default().to_string(), "no games");
+}
+
+#[test]
+fn a_clean_sweep_has_a_finite_elo() {
+    let sweep = Score {
+        wins: 20,
+        ..Score::default()
+    };
+    let elo = sweep.elo().unwrap();
+    assert!(elo.difference.is_finite() && elo.lower.is_finite() && elo.upper.is_finite(), "{:?}", elo);
+    assert!(elo.difference > 0.0);
+    assert!(!sweep.to_string().contains("inf"), "{}", sweep);
+
+    let whitewash = Score {
+        losses: 20,
+        ..Score::default()
+    };
+    let elo = whitewash.elo().unwrap();
+    assert!(elo.difference.is_finite() && elo.difference < 0.0, "{:?}", elo);
+}
+
+#[test]
+fn a_one_sided_run_stops_the_sprt() {
+    let sprt = sprt();
+    assert_eq!(sprt.test(&Score::default()), SprtOutcome::Continue);
+
+    let stopped = (1..1000).find(|&wins| {
+        sprt.test(&Score {
+            wins,
+            ..Score::default()
+        }) != SprtOutcome::Continue
+    });
+    let wins = stopped.expect("the SPRT never stopped");
+    assert_eq!(
+        sprt.test(&Score {
+            wins,
+            ..Score::default()
+        }),
+        SprtOutcome::AcceptH1
+    );
+
+    let losses = Score {
+        losses: wins,
+        ..Score::default()
+    };
+    assert_eq!(sprt.test(&losses), SprtOutcome::AcceptH0);
+}
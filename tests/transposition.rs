@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use battlesnake_rusty::session::{GameSession, Sessions};
+use battlesnake_rusty::strategy::{Rusty, Strategy};
+use battlesnake_rusty::transposition::{self, TranspositionTable};
+use battlesnake_rusty::weights::Profile;
+use battlesnake_rusty::{Move, ascii, logic, ponder};
+
+mod common;
+
+#[test]
+fn remembers_scores_by_position_and_depth() {
+    let table = TranspositionTable::new(1000);
+    assert_eq!(table.capacity(), 1024);
+
+    table.store(42, 3, 17.5);
+    assert_eq!(table.get(42, 3), Some(17.5));
+    assert_eq!(table.get(42, 4), None);
+    assert_eq!(table.get(43, 3), None);
+
+    table.store(42, 3, 2.0);
+    assert_eq!(table.get(42, 3), Some(2.0));
+}
+
+#[test]
+fn keys_follow_the_order_of_a_body() {
+    let clockwise = ascii::parse(
+        "
+        # # # # #
+        # A a . #
+        # a a . #
+        # . . . #
+        # # # # #
+        ",
+    )
+    .unwrap();
+    // The same four squares, coiled the other way round
+    let mut anticlockwise = clockwise.clone();
+    anticlockwise.board.snakes[0].body.swap(1, 3);
+
+    assert_ne!(
+        transposition::board_key(&clockwise.board),
+        transposition::board_key(&anticlockwise.board)
+    );
+}
+
+#[test]
+fn scores_are_the_same_from_a_warm_table() {
+    let state = common::four_snakes();
+    let session = common::session(&state);
+
+    let scores = |session: &GameSession| {
+        let (opponents, table) = (&session.opponents, &session.transpositions);
+        logic::move_safety_scores(&state.board, &state.you, &Move::ALL, 6, common::risk_limit(), opponents, table, 1)
+    };
+    let cold = scores(&session);
+    let warm = scores(&session);
+    assert_eq!(cold, warm);
+    assert!(cold[&Move::Left] > 0.0);
+}
+
+#[test]
+fn explaining_leaves_the_live_game_alone() {
+    let state = common::four_snakes();
+    let sessions = Sessions::default();
+    let live = sessions.get(&state.game, &state.you);
+    *live.lock().unwrap() = common::session(&state);
+    let pondering = ponder::start(&state, Move::Left, &live.lock().unwrap(), common::risk_limit()).unwrap();
+    live.lock().unwrap().pondering = Some(pondering.clone());
+
+    let mut copy = sessions.peek(&state.game, &state.you).unwrap();
+    assert!(copy.pondering.is_none());
+    assert!(!Arc::ptr_eq(&copy.transpositions, &live.lock().unwrap().transpositions));
+    Rusty::new(Profile::default()).explain(&state, &mut copy);
+
+    // The game keeps its own pondering, on its own table
+    let live = live.lock().unwrap();
+    assert!(live.pondering.is_some());
+    assert!(!Arc::ptr_eq(&copy.transpositions, &live.transpositions));
+    pondering.stop();
+}
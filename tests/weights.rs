@@ -0,0 +1,109 @@
+use battlesnake_rusty::session::GameSession;
+use battlesnake_rusty::weights::{Profile, Weights};
+use battlesnake_rusty::{Move, ascii, logic};
+
+#[test]
+fn rulesets_override_the_defaults() {
+    let profile = Profile::parse(
+        "
+        [default]
+        low_health = 40
+
+        [default.evaluation]
+        health = 1.5
+
+        [royale]
+        wall_buffer = 3
+
+        [royale.evaluation]
+        space = 3.0
+        ",
+    )
+    .unwrap();
+
+    let standard = profile.for_ruleset("standard");
+    assert_eq!(standard.low_health, 40);
+    assert_eq!(standard.look_ahead_depth, Weights::default().look_ahead_depth);
+
+    let royale = profile.for_ruleset("royale");
+    assert_eq!(royale.low_health, 40);
+    assert_eq!(royale.wall_buffer, 3);
+    assert_eq!(royale.evaluation.health, 1.5);
+    assert_eq!(royale.evaluation.space, 3.0);
+
+    assert_eq!(Profile::parse(&profile.to_toml().unwrap()).unwrap(), profile);
+    assert_eq!(Profile::load("no such file.toml").unwrap(), Profile::default());
+}
+
+#[test]
+fn rejects_unknown_weights() {
+    let error = Profile::parse("[constrictor]\nlow_heath = 10").unwrap_err();
+    assert!(error.contains("[constrictor]"), "{}", error);
+}
+
+#[test]
+fn low_health_decides_when_to_eat() {
+    let state = ascii::parse(
+        "
+        # # # # # # # # #
+        # . . . . . . . #
+        # . . . . . . . #
+        # . . . . . . . #
+        # * . . A a a . #
+        # . . . . . . . #
+        # . . . . . . . #
+        # . . . . . . . #
+        # # # # # # # # #
+        ",
+    )
+    .unwrap();
+    let explain = |low_health| {
+        let weights = Weights {
+            low_health,
+            ..Weights::default()
+        };
+        let mut session = GameSession::default();
+        logic::explain(&state.game, &state.turn, &state.board, &state.you, &mut session, &weights)
+    };
+
+    let hungry = explain(state.you.health + 1);
+    assert!(hungry.trace.health_is_low);
+    assert_eq!(hungry.trace.food_seeking_moves, vec![Move::Left]);
+    assert!(!explain(state.you.health).trace.health_is_low);
+}
+
+#[test]
+fn the_shipped_profile_holds_the_built_in_weights() {
+    let profile = Profile::load(concat!(env!("CARGO_MANIFEST_DIR"), "/Weights.toml")).unwrap();
+    assert_eq!(profile, Profile::default());
+}
+
+#[test]
+fn the_wall_buffer_applies_to_both_sides() {
+    // Three squares wide, so a buffer of three reaches every wall
+    let state = ascii::parse(
+        "
+        # # # # #
+        # . . . #
+        # . . . #
+        # . . A #
+        # . . a #
+        # . . a #
+        # # # # #
+        ",
+    )
+    .unwrap();
+    let weights = Weights {
+        wall_buffer: 3,
+        ..Weights::default()
+    };
+    let mut session = GameSession::default();
+    let explanation = logic::explain(&state.game, &state.turn, &state.board, &state.you, &mut session, &weights);
+
+    // Right is into the wall, which rules it out before the look-ahead
+    assert!(!explanation.trace.is_move_safe[&Move::Right]);
+    assert!(!explanation.trace.safety_scores.contains_key(&Move::Right));
+    for move_dir in Move::ALL {
+        assert!(!explanation.trace.is_move_desirable[&move_dir], "{:?} is desirable", move_dir);
+    }
+}